@@ -1,7 +1,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 
 use yaxpeax_arch::{Arch, Decoder, U8Reader};
-use yaxpeax_mips::{Instruction, RISCV}; //, Opcode};
+use yaxpeax_riscv::{BufferSink, DisplayOptions, Instruction, RISCV}; //, Opcode};
 
 fn decode_single(insn: u32) -> Instruction {
     let insn = insn.to_le_bytes();
@@ -12,6 +12,7 @@ fn decode_single(insn: u32) -> Instruction {
         .unwrap()
 }
 
+#[allow(dead_code)]
 fn decode_multi(insns: &[u32]) {
     for insn in insns {
         decode_single(*insn);
@@ -27,7 +28,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             decode_single(black_box(0x00c55613));
         })
     });
-    
+
     group.throughput(Throughput::Elements(2));
     group.bench_function("decode sw/srli", |b| {
         b.iter(|| {
@@ -35,6 +36,52 @@ fn criterion_benchmark(c: &mut Criterion) {
             decode_single(black_box(0x02a12423));
         })
     });
+    group.finish();
+
+    let mut group = c.benchmark_group("display");
+    let insns = [
+        decode_single(0x00c55613),
+        decode_single(0x02a12423),
+        decode_single(0xfef718e3),
+        decode_single(0xc0002573),
+    ];
+    group.throughput(Throughput::Elements(insns.len() as u64));
+
+    group.bench_function("format!", |b| {
+        b.iter(|| {
+            for insn in insns.iter() {
+                black_box(format!("{}", black_box(insn)));
+            }
+        })
+    });
+
+    group.bench_function("String sink", |b| {
+        let mut text = String::with_capacity(64);
+        b.iter(|| {
+            for insn in insns.iter() {
+                text.clear();
+                black_box(insn)
+                    .display_with(DisplayOptions::new())
+                    .write_to(&mut text)
+                    .unwrap();
+                black_box(&text);
+            }
+        })
+    });
+
+    group.bench_function("buffer sink", |b| {
+        let mut buf = [0u8; 64];
+        b.iter(|| {
+            for insn in insns.iter() {
+                let mut sink = BufferSink::new(&mut buf);
+                black_box(insn)
+                    .display_with(DisplayOptions::new())
+                    .write_to(&mut sink)
+                    .unwrap();
+                black_box(sink.as_str());
+            }
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,151 @@
+//! Reading the target ISA out of the `.riscv.attributes` section of an ELF file.
+//!
+//! The section holds build attributes in the generic ELF attributes format: a format version
+//! byte, then per-vendor subsections of tagged values. The `riscv` vendor subsection records
+//! the ISA the file was compiled for as `Tag_RISCV_arch`, an ISA string such as
+//! `rv64i2p1_m2p0_a2p1_f2p2_d2p2_c2p0_zicsr2p0`.
+
+use std::fmt;
+
+use crate::{IsaStringError, RiscVDecoder};
+
+const FORMAT_VERSION: u8 = b'A';
+const TAG_FILE: u64 = 1;
+const TAG_RISCV_ARCH: u64 = 5;
+
+/// Why a `.riscv.attributes` section could not be turned into a [`RiscVDecoder`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AttributesError {
+    /// The section is truncated or otherwise not in the ELF attributes format.
+    Malformed,
+    /// The section has no `Tag_RISCV_arch` attribute.
+    MissingArch,
+    /// The `Tag_RISCV_arch` attribute is not a usable ISA string.
+    Isa(IsaStringError),
+}
+
+impl fmt::Display for AttributesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributesError::Malformed => write!(f, "malformed attributes section"),
+            AttributesError::MissingArch => write!(f, "no Tag_RISCV_arch attribute"),
+            AttributesError::Isa(e) => write!(f, "bad Tag_RISCV_arch attribute: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AttributesError {}
+
+impl From<IsaStringError> for AttributesError {
+    fn from(e: IsaStringError) -> Self {
+        AttributesError::Isa(e)
+    }
+}
+
+/// A cursor over the little-endian contents of an attributes section.
+struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn u32(&mut self) -> Result<u32, AttributesError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn uleb128(&mut self) -> Result<u64, AttributesError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(AttributesError::Malformed)
+    }
+
+    fn ntbs(&mut self) -> Result<&'a str, AttributesError> {
+        let len = self
+            .data
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(AttributesError::Malformed)?;
+        let s = std::str::from_utf8(&self.data[..len]).map_err(|_| AttributesError::Malformed)?;
+        self.data = &self.data[len + 1..];
+        Ok(s)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AttributesError> {
+        if self.data.len() < len {
+            return Err(AttributesError::Malformed);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    /// Take a block whose `u32` length, already read, counts `header` bytes before the contents.
+    fn block(&mut self, len: u32, header: usize) -> Result<Cursor<'a>, AttributesError> {
+        let len = (len as usize)
+            .checked_sub(header)
+            .ok_or(AttributesError::Malformed)?;
+        Ok(Cursor {
+            data: self.take(len)?,
+        })
+    }
+}
+
+/// The `Tag_RISCV_arch` ISA string recorded in `section`, the contents of a `.riscv.attributes`
+/// ELF section.
+pub fn arch_attribute(section: &[u8]) -> Result<&str, AttributesError> {
+    let mut cursor = Cursor { data: section };
+    if cursor.take(1)?[0] != FORMAT_VERSION {
+        return Err(AttributesError::Malformed);
+    }
+
+    while !cursor.data.is_empty() {
+        let len = cursor.u32()?;
+        let mut subsection = cursor.block(len, 4)?;
+        if subsection.ntbs()? != "riscv" {
+            continue;
+        }
+
+        while !subsection.data.is_empty() {
+            // the length counts the tag and itself
+            let start = subsection.data.len();
+            let tag = subsection.uleb128()?;
+            let len = subsection.u32()?;
+            let mut attributes = subsection.block(len, start - subsection.data.len())?;
+            if tag != TAG_FILE {
+                // attributes of individual sections or symbols
+                continue;
+            }
+
+            while !attributes.data.is_empty() {
+                let tag = attributes.uleb128()?;
+                if tag == TAG_RISCV_ARCH {
+                    return attributes.ntbs();
+                }
+                // odd tags carry strings, even tags integers
+                if tag % 2 == 1 {
+                    attributes.ntbs()?;
+                } else {
+                    attributes.uleb128()?;
+                }
+            }
+        }
+    }
+
+    Err(AttributesError::MissingArch)
+}
+
+impl RiscVDecoder {
+    /// Construct a decoder for the ISA recorded in `section`, the contents of a
+    /// `.riscv.attributes` ELF section.
+    ///
+    /// See [`RiscVDecoder::from_isa_string`].
+    pub fn from_attributes(section: &[u8]) -> Result<RiscVDecoder, AttributesError> {
+        Ok(RiscVDecoder::from_isa_string(arch_attribute(section)?)?)
+    }
+}
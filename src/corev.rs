@@ -0,0 +1,312 @@
+//! CORE-V vendor extensions, as implemented by the OpenHW CV32E40P.
+//!
+//! These occupy the custom-0, custom-1 and custom-3 major opcodes and are only decoded when the
+//! corresponding [`Extension`] has been enabled on the [`RiscVDecoder`].
+
+use crate::{DecodeError, Extension, Instruction, Opcode, OperandSpec, RiscVDecoder};
+
+const RD_RS1: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_RS1_RS2: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Rs2,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_RS1_UIMM5: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Uimm5,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_POSTINC: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::PostIncRs1Rs2,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_INDEXED: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::BaseIndexRs1Rs2,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RS2_POSTINC: [OperandSpec; 5] = [
+    OperandSpec::Rs2,
+    OperandSpec::PostIncRs1Rd,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RS2_INDEXED: [OperandSpec; 5] = [
+    OperandSpec::Rs2,
+    OperandSpec::BaseIndexRs1Rd,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+
+impl RiscVDecoder {
+    /// Decode `word`, an instruction in one of the custom major opcodes, as a CORE-V vendor
+    /// instruction.
+    pub(crate) fn decode_corev(
+        &self,
+        instruction: &mut Instruction,
+        word: u32,
+    ) -> Result<(), DecodeError> {
+        let opc = word & 0b111_1111;
+        let funct3 = (word >> 12) & 0b111;
+        let funct7 = (word >> 25) & 0b111_1111;
+        let rs2 = (word >> 20) & 0b11111;
+        let rs1 = (word >> 15) & 0b11111;
+
+        let (extension, opcode, operands) = match (opc, funct3) {
+            (0b000_1011, _) => {
+                // post-incrementing loads with an immediate increment
+                let opcode = match funct3 {
+                    0b000 => Opcode::CV_LB,
+                    0b001 => Opcode::CV_LH,
+                    0b010 => Opcode::CV_LW,
+                    0b100 => Opcode::CV_LBU,
+                    0b101 => Opcode::CV_LHU,
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                };
+                let operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::PostIncRs1I,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
+                (Extension::XCVmem, opcode, operands)
+            }
+            (0b010_1011, 0b000..=0b010) => {
+                // post-incrementing stores with an immediate increment
+                let opcode = match funct3 {
+                    0b000 => Opcode::CV_SB,
+                    0b001 => Opcode::CV_SH,
+                    _ => Opcode::CV_SW,
+                };
+                let operands = [
+                    OperandSpec::Rs2,
+                    OperandSpec::PostIncRs1S,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
+                (Extension::XCVmem, opcode, operands)
+            }
+            (0b010_1011, 0b011) => match funct7 {
+                0b000_0000 => (Extension::XCVmem, Opcode::CV_LB, RD_POSTINC),
+                0b000_0001 => (Extension::XCVmem, Opcode::CV_LH, RD_POSTINC),
+                0b000_0010 => (Extension::XCVmem, Opcode::CV_LW, RD_POSTINC),
+                0b000_1000 => (Extension::XCVmem, Opcode::CV_LBU, RD_POSTINC),
+                0b000_1001 => (Extension::XCVmem, Opcode::CV_LHU, RD_POSTINC),
+                0b000_0100 => (Extension::XCVmem, Opcode::CV_LB, RD_INDEXED),
+                0b000_0101 => (Extension::XCVmem, Opcode::CV_LH, RD_INDEXED),
+                0b000_0110 => (Extension::XCVmem, Opcode::CV_LW, RD_INDEXED),
+                0b000_1100 => (Extension::XCVmem, Opcode::CV_LBU, RD_INDEXED),
+                0b000_1101 => (Extension::XCVmem, Opcode::CV_LHU, RD_INDEXED),
+                0b001_0000 => (Extension::XCVmem, Opcode::CV_SB, RS2_POSTINC),
+                0b001_0001 => (Extension::XCVmem, Opcode::CV_SH, RS2_POSTINC),
+                0b001_0010 => (Extension::XCVmem, Opcode::CV_SW, RS2_POSTINC),
+                0b001_0100 => (Extension::XCVmem, Opcode::CV_SB, RS2_INDEXED),
+                0b001_0101 => (Extension::XCVmem, Opcode::CV_SH, RS2_INDEXED),
+                0b001_0110 => (Extension::XCVmem, Opcode::CV_SW, RS2_INDEXED),
+                0b010_1000 => (Extension::XCValu, Opcode::CV_ABS, RD_RS1),
+                0b010_1001 => (Extension::XCValu, Opcode::CV_SLET, RD_RS1_RS2),
+                0b010_1010 => (Extension::XCValu, Opcode::CV_SLETU, RD_RS1_RS2),
+                0b010_1011 => (Extension::XCValu, Opcode::CV_MIN, RD_RS1_RS2),
+                0b010_1100 => (Extension::XCValu, Opcode::CV_MINU, RD_RS1_RS2),
+                0b010_1101 => (Extension::XCValu, Opcode::CV_MAX, RD_RS1_RS2),
+                0b010_1110 => (Extension::XCValu, Opcode::CV_MAXU, RD_RS1_RS2),
+                0b011_0000 => (Extension::XCValu, Opcode::CV_EXTHS, RD_RS1),
+                0b011_0001 => (Extension::XCValu, Opcode::CV_EXTHZ, RD_RS1),
+                0b011_0010 => (Extension::XCValu, Opcode::CV_EXTBS, RD_RS1),
+                0b011_0011 => (Extension::XCValu, Opcode::CV_EXTBZ, RD_RS1),
+                0b011_1000 => (Extension::XCValu, Opcode::CV_CLIP, RD_RS1_UIMM5),
+                0b011_1001 => (Extension::XCValu, Opcode::CV_CLIPU, RD_RS1_UIMM5),
+                0b011_1010 => (Extension::XCValu, Opcode::CV_CLIPR, RD_RS1_RS2),
+                0b011_1011 => (Extension::XCValu, Opcode::CV_CLIPUR, RD_RS1_RS2),
+                0b100_1000 => (Extension::XCVmac, Opcode::CV_MAC, RD_RS1_RS2),
+                0b100_1001 => (Extension::XCVmac, Opcode::CV_MSU, RD_RS1_RS2),
+                _ => Err(DecodeError::InvalidOpcode)?,
+            },
+            (0b010_1011, 0b100) => {
+                // hardware loop setup; the loop index L is in bit 7
+                let funct4 = (word >> 8) & 0b1111;
+                let imm = word >> 20;
+
+                let opcode = match funct4 {
+                    0b0000 if rs1 == 0 => Opcode::CV_STARTI,
+                    0b0001 if imm == 0 => Opcode::CV_START,
+                    0b0010 if rs1 == 0 => Opcode::CV_ENDI,
+                    0b0011 if imm == 0 => Opcode::CV_END,
+                    0b0100 if rs1 == 0 => Opcode::CV_COUNTI,
+                    0b0101 if imm == 0 => Opcode::CV_COUNT,
+                    0b0110 => Opcode::CV_SETUPI,
+                    0b0111 => Opcode::CV_SETUP,
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                };
+                let operands = match opcode {
+                    Opcode::CV_STARTI => [
+                        OperandSpec::LoopL,
+                        OperandSpec::LoopOffset12,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                    Opcode::CV_START => [
+                        OperandSpec::LoopL,
+                        OperandSpec::Rs1,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                    Opcode::CV_ENDI => [
+                        OperandSpec::LoopL,
+                        OperandSpec::LoopOffset12,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                    Opcode::CV_END => [
+                        OperandSpec::LoopL,
+                        OperandSpec::Rs1,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                    Opcode::CV_COUNTI => [
+                        OperandSpec::LoopL,
+                        OperandSpec::Uimm12,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                    Opcode::CV_COUNT => [
+                        OperandSpec::LoopL,
+                        OperandSpec::Rs1,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                    Opcode::CV_SETUPI => [
+                        OperandSpec::LoopL,
+                        OperandSpec::Uimm12,
+                        OperandSpec::LoopOffset5,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                    _ => [
+                        OperandSpec::LoopL,
+                        OperandSpec::Rs1,
+                        OperandSpec::LoopOffset12,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ],
+                };
+                (Extension::XCVhwlp, opcode, operands)
+            }
+            (0b111_1011, _) => {
+                // packed SIMD; bit 26 selects the (unsupported) comparison and dot product forms,
+                // and bit 25 only carries immediate bits.
+                if funct7 & 0b11 != 0 {
+                    Err(DecodeError::InvalidOpcode)?
+                }
+
+                let funct5 = funct7 >> 2;
+                let opcode = match (funct5, funct3) {
+                    (0b00000, 0b000) => Opcode::CV_ADD_H,
+                    (0b00000, 0b001) => Opcode::CV_ADD_B,
+                    (0b00000, 0b100) => Opcode::CV_ADD_SC_H,
+                    (0b00000, 0b101) => Opcode::CV_ADD_SC_B,
+                    (0b00001, 0b000) => Opcode::CV_SUB_H,
+                    (0b00001, 0b001) => Opcode::CV_SUB_B,
+                    (0b00001, 0b100) => Opcode::CV_SUB_SC_H,
+                    (0b00001, 0b101) => Opcode::CV_SUB_SC_B,
+                    (0b00010, 0b000) => Opcode::CV_AVG_H,
+                    (0b00010, 0b001) => Opcode::CV_AVG_B,
+                    (0b00010, 0b100) => Opcode::CV_AVG_SC_H,
+                    (0b00010, 0b101) => Opcode::CV_AVG_SC_B,
+                    (0b00011, 0b000) => Opcode::CV_AVGU_H,
+                    (0b00011, 0b001) => Opcode::CV_AVGU_B,
+                    (0b00011, 0b100) => Opcode::CV_AVGU_SC_H,
+                    (0b00011, 0b101) => Opcode::CV_AVGU_SC_B,
+                    (0b00100, 0b000) => Opcode::CV_MIN_H,
+                    (0b00100, 0b001) => Opcode::CV_MIN_B,
+                    (0b00100, 0b100) => Opcode::CV_MIN_SC_H,
+                    (0b00100, 0b101) => Opcode::CV_MIN_SC_B,
+                    (0b00101, 0b000) => Opcode::CV_MINU_H,
+                    (0b00101, 0b001) => Opcode::CV_MINU_B,
+                    (0b00101, 0b100) => Opcode::CV_MINU_SC_H,
+                    (0b00101, 0b101) => Opcode::CV_MINU_SC_B,
+                    (0b00110, 0b000) => Opcode::CV_MAX_H,
+                    (0b00110, 0b001) => Opcode::CV_MAX_B,
+                    (0b00110, 0b100) => Opcode::CV_MAX_SC_H,
+                    (0b00110, 0b101) => Opcode::CV_MAX_SC_B,
+                    (0b00111, 0b000) => Opcode::CV_MAXU_H,
+                    (0b00111, 0b001) => Opcode::CV_MAXU_B,
+                    (0b00111, 0b100) => Opcode::CV_MAXU_SC_H,
+                    (0b00111, 0b101) => Opcode::CV_MAXU_SC_B,
+                    (0b01000, 0b000) => Opcode::CV_SRL_H,
+                    (0b01000, 0b001) => Opcode::CV_SRL_B,
+                    (0b01000, 0b100) => Opcode::CV_SRL_SC_H,
+                    (0b01000, 0b101) => Opcode::CV_SRL_SC_B,
+                    (0b01001, 0b000) => Opcode::CV_SRA_H,
+                    (0b01001, 0b001) => Opcode::CV_SRA_B,
+                    (0b01001, 0b100) => Opcode::CV_SRA_SC_H,
+                    (0b01001, 0b101) => Opcode::CV_SRA_SC_B,
+                    (0b01010, 0b000) => Opcode::CV_SLL_H,
+                    (0b01010, 0b001) => Opcode::CV_SLL_B,
+                    (0b01010, 0b100) => Opcode::CV_SLL_SC_H,
+                    (0b01010, 0b101) => Opcode::CV_SLL_SC_B,
+                    (0b01011, 0b000) => Opcode::CV_OR_H,
+                    (0b01011, 0b001) => Opcode::CV_OR_B,
+                    (0b01011, 0b100) => Opcode::CV_OR_SC_H,
+                    (0b01011, 0b101) => Opcode::CV_OR_SC_B,
+                    (0b01100, 0b000) => Opcode::CV_XOR_H,
+                    (0b01100, 0b001) => Opcode::CV_XOR_B,
+                    (0b01100, 0b100) => Opcode::CV_XOR_SC_H,
+                    (0b01100, 0b101) => Opcode::CV_XOR_SC_B,
+                    (0b01101, 0b000) => Opcode::CV_AND_H,
+                    (0b01101, 0b001) => Opcode::CV_AND_B,
+                    (0b01101, 0b100) => Opcode::CV_AND_SC_H,
+                    (0b01101, 0b101) => Opcode::CV_AND_SC_B,
+                    (0b01110, 0b000) if rs2 == 0 => Opcode::CV_ABS_H,
+                    (0b01110, 0b001) if rs2 == 0 => Opcode::CV_ABS_B,
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                };
+                let operands = match opcode {
+                    Opcode::CV_ABS_H | Opcode::CV_ABS_B => RD_RS1,
+                    _ => RD_RS1_RS2,
+                };
+                (Extension::XCVsimd, opcode, operands)
+            }
+            _ => Err(DecodeError::InvalidOpcode)?,
+        };
+
+        if !self.has_extension(extension) {
+            Err(DecodeError::ExtensionNotEnabled(extension))?
+        }
+
+        // single-source operations leave rs2 reserved as zero
+        if operands == RD_RS1 && rs2 != 0 {
+            Err(DecodeError::InvalidOpcode)?
+        }
+
+        instruction.opcode = opcode;
+        instruction.operands = operands;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! User-pluggable decoding of the custom major opcodes.
+//!
+//! The custom-0 through custom-3 major opcodes are reserved by the base ISA for non-standard
+//! extensions. A [`CustomDecoder`] registered with [`RiscVDecoder::with_custom_decoder`] is
+//! offered every instruction in these spaces before the built-in vendor extensions are consulted.
+
+use crate::{DecodeError, Extension, Instruction, Opcode, Operand, OperandSpec, RiscVDecoder};
+
+/// One of the major opcodes reserved for custom extensions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CustomSpace {
+    /// Major opcode `0b000_1011`
+    Custom0,
+    /// Major opcode `0b010_1011`
+    Custom1,
+    /// Major opcode `0b101_1011`
+    Custom2,
+    /// Major opcode `0b111_1011`
+    Custom3,
+}
+
+/// An instruction decoded by a [`CustomDecoder`].
+///
+/// Operands are listed in assembly order and are displayed and iterated over like those of any
+/// other instruction; unused trailing slots are `None`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CustomInstruction {
+    pub mnemonic: &'static str,
+    pub operands: [Option<Operand>; 5],
+}
+
+impl CustomInstruction {
+    pub fn new(mnemonic: &'static str) -> Self {
+        CustomInstruction {
+            mnemonic,
+            operands: [None; 5],
+        }
+    }
+
+    /// Append `operand` after any operands already present.
+    ///
+    /// # Panics
+    ///
+    /// If the instruction already has five operands.
+    pub fn with_operand(mut self, operand: Operand) -> Self {
+        let slot = self
+            .operands
+            .iter_mut()
+            .find(|op| op.is_none())
+            .expect("custom instructions have at most five operands");
+        *slot = Some(operand);
+        self
+    }
+}
+
+/// A decoder for instructions in the custom major opcodes.
+pub trait CustomDecoder {
+    /// Decode `word`, a 32-bit instruction in the `space` major opcode.
+    ///
+    /// Returning `None` leaves the word to the built-in vendor extensions enabled on the
+    /// [`RiscVDecoder`], if any.
+    fn decode(&self, space: CustomSpace, word: u32) -> Option<CustomInstruction>;
+}
+
+impl RiscVDecoder {
+    /// Decode `word`, an instruction in one of the custom major opcodes.
+    pub(crate) fn decode_custom(
+        &self,
+        instruction: &mut Instruction,
+        word: u32,
+    ) -> Result<(), DecodeError> {
+        let space = match word & 0b111_1111 {
+            0b000_1011 => CustomSpace::Custom0,
+            0b010_1011 => CustomSpace::Custom1,
+            0b101_1011 => CustomSpace::Custom2,
+            0b111_1011 => CustomSpace::Custom3,
+            _ => unreachable!("not a custom major opcode"),
+        };
+
+        if let Some(custom) = self.custom.as_ref() {
+            if let Some(decoded) = custom.decode(space, word) {
+                instruction.opcode = Opcode::Custom(decoded.mnemonic);
+                instruction.operands = [
+                    OperandSpec::Custom(0),
+                    OperandSpec::Custom(1),
+                    OperandSpec::Custom(2),
+                    OperandSpec::Custom(3),
+                    OperandSpec::Custom(4),
+                ];
+                instruction.custom_operands = decoded.operands;
+                return Ok(());
+            }
+        }
+
+        match space {
+            CustomSpace::Custom0 => self.decode_custom0(instruction, word),
+            CustomSpace::Custom1 | CustomSpace::Custom3 => self.decode_corev(instruction, word),
+            CustomSpace::Custom2 => Err(DecodeError::InvalidOpcode),
+        }
+    }
+
+    /// Decode `word`, an instruction in the custom-0 major opcode, which the T-Head and CORE-V
+    /// extensions share.
+    ///
+    /// Their encodings overlap, so the word is looked up in both tables. It is the instruction of
+    /// whichever table decodes it, T-Head's if both do. Otherwise the error is that of the table
+    /// with an instruction for the word, and if both have one, that of the vendor with any
+    /// extension enabled; a word both vendors could mean and neither is enabled for is not
+    /// attributed to either.
+    fn decode_custom0(&self, instruction: &mut Instruction, word: u32) -> Result<(), DecodeError> {
+        let thead = self.decode_thead(instruction, word);
+        if thead.is_ok() {
+            return thead;
+        }
+        let corev = self.decode_corev(instruction, word);
+        let enabled = |vendor: &[Extension]| vendor.iter().any(|ext| self.has_extension(*ext));
+        match (thead, corev) {
+            (_, Ok(())) => Ok(()),
+            (Err(DecodeError::InvalidOpcode), corev) => corev,
+            (thead, Err(DecodeError::InvalidOpcode)) => thead,
+            (
+                Err(DecodeError::ExtensionNotEnabled(thead)),
+                Err(DecodeError::ExtensionNotEnabled(corev)),
+            ) => match (enabled(&Extension::THEAD), enabled(&Extension::COREV)) {
+                (true, false) => Err(DecodeError::ExtensionNotEnabled(thead)),
+                (false, true) => Err(DecodeError::ExtensionNotEnabled(corev)),
+                _ => Err(DecodeError::InvalidOpcode),
+            },
+            // an enabled table rejected the word's operands
+            (Err(DecodeError::ExtensionNotEnabled(_)), corev) => corev,
+            (thead, _) => thead,
+        }
+    }
+}
@@ -1,146 +1,1660 @@
 use std::fmt;
+use std::ops::Range;
 
-use crate::{Instruction, Opcode, Operand};
+use yaxpeax_arch::{Colorize, NoColors, ShowContextual, YaxColors};
 
-const REG_NAMES: [&'static str; 32] = [
+use crate::{FieldSpec, Instruction, Opcode, Operand, OperandSpec, RoundingMode, Xlen};
+
+const REG_NAMES: [&str; 32] = [
     "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
     "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
     "t5", "t6",
 ];
 
+const FREG_NAMES: [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2",
+    "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9",
+    "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+
+const CSR_FFLAGS: u16 = 0x001;
+const CSR_FRM: u16 = 0x002;
+const CSR_FCSR: u16 = 0x003;
+const CSR_CYCLE: u16 = 0xc00;
+const CSR_TIME: u16 = 0xc01;
+const CSR_INSTRET: u16 = 0xc02;
+
+/// The name of control and status register `csr`, if it has one.
+fn csr_name(csr: u16) -> Option<&'static str> {
+    const HPMCOUNTER: [&str; 29] = [
+        "hpmcounter3",
+        "hpmcounter4",
+        "hpmcounter5",
+        "hpmcounter6",
+        "hpmcounter7",
+        "hpmcounter8",
+        "hpmcounter9",
+        "hpmcounter10",
+        "hpmcounter11",
+        "hpmcounter12",
+        "hpmcounter13",
+        "hpmcounter14",
+        "hpmcounter15",
+        "hpmcounter16",
+        "hpmcounter17",
+        "hpmcounter18",
+        "hpmcounter19",
+        "hpmcounter20",
+        "hpmcounter21",
+        "hpmcounter22",
+        "hpmcounter23",
+        "hpmcounter24",
+        "hpmcounter25",
+        "hpmcounter26",
+        "hpmcounter27",
+        "hpmcounter28",
+        "hpmcounter29",
+        "hpmcounter30",
+        "hpmcounter31",
+    ];
+
+    let name = match csr {
+        // unprivileged floating-point, vector and counter CSRs
+        0x001 => "fflags",
+        0x002 => "frm",
+        0x003 => "fcsr",
+        0x008 => "vstart",
+        0x009 => "vxsat",
+        0x00a => "vxrm",
+        0x00f => "vcsr",
+        0x015 => "seed",
+        0xc00 => "cycle",
+        0xc01 => "time",
+        0xc02 => "instret",
+        0xc03..=0xc1f => HPMCOUNTER[csr as usize - 0xc03],
+        0xc20 => "vl",
+        0xc21 => "vtype",
+        0xc22 => "vlenb",
+        0xc80 => "cycleh",
+        0xc81 => "timeh",
+        0xc82 => "instreth",
+        // supervisor CSRs
+        0x100 => "sstatus",
+        0x104 => "sie",
+        0x105 => "stvec",
+        0x106 => "scounteren",
+        0x10a => "senvcfg",
+        0x140 => "sscratch",
+        0x141 => "sepc",
+        0x142 => "scause",
+        0x143 => "stval",
+        0x144 => "sip",
+        0x14d => "stimecmp",
+        0x180 => "satp",
+        0x5a8 => "scontext",
+        // hypervisor CSRs
+        0x600 => "hstatus",
+        0x602 => "hedeleg",
+        0x603 => "hideleg",
+        0x604 => "hie",
+        0x605 => "htimedelta",
+        0x606 => "hcounteren",
+        0x607 => "hgeie",
+        0x60a => "henvcfg",
+        0x643 => "htval",
+        0x644 => "hip",
+        0x645 => "hvip",
+        0x64a => "htinst",
+        0x680 => "hgatp",
+        0xe12 => "hgeip",
+        0x200 => "vsstatus",
+        0x204 => "vsie",
+        0x205 => "vstvec",
+        0x240 => "vsscratch",
+        0x241 => "vsepc",
+        0x242 => "vscause",
+        0x243 => "vstval",
+        0x244 => "vsip",
+        0x280 => "vsatp",
+        // machine CSRs
+        0xf11 => "mvendorid",
+        0xf12 => "marchid",
+        0xf13 => "mimpid",
+        0xf14 => "mhartid",
+        0xf15 => "mconfigptr",
+        0x300 => "mstatus",
+        0x301 => "misa",
+        0x302 => "medeleg",
+        0x303 => "mideleg",
+        0x304 => "mie",
+        0x305 => "mtvec",
+        0x306 => "mcounteren",
+        0x30a => "menvcfg",
+        0x310 => "mstatush",
+        0x31a => "menvcfgh",
+        0x320 => "mcountinhibit",
+        0x340 => "mscratch",
+        0x341 => "mepc",
+        0x342 => "mcause",
+        0x343 => "mtval",
+        0x344 => "mip",
+        0x34a => "mtinst",
+        0x34b => "mtval2",
+        0x747 => "mseccfg",
+        0xb00 => "mcycle",
+        0xb02 => "minstret",
+        // debug CSRs
+        0x7a0 => "tselect",
+        0x7a1 => "tdata1",
+        0x7a2 => "tdata2",
+        0x7a3 => "tdata3",
+        0x7a8 => "mcontext",
+        0x7b0 => "dcsr",
+        0x7b1 => "dpc",
+        0x7b2 => "dscratch0",
+        0x7b3 => "dscratch1",
+        _ => return None,
+    };
+    Some(name)
+}
+
+impl Instruction {
+    /// The pseudo-instruction shown in place of this instruction, as a mnemonic and operands.
+    ///
+    /// Where several pseudo-instructions could describe an instruction, this picks the one GNU
+    /// objdump does.
+    pub(crate) fn pseudo(&self) -> Option<(&'static str, [Option<Operand>; 2])> {
+        let rd = self.field(FieldSpec::Rd) as u8;
+        let rs1 = self.field(FieldSpec::Rs1) as u8;
+        let rs2 = self.field(FieldSpec::Rs2) as u8;
+        let imm = self.field(FieldSpec::Imm12I) as i32;
+        let csr = self.field(FieldSpec::Csr) as u16;
+
+        let reg = |r: u8| Some(Operand::Reg(r));
+        let freg = |r: u8| Some(Operand::FReg(r));
+        let csr_op = Some(Operand::Csr(csr));
+        let zimm = Some(Operand::Imm(rs1 as i32));
+        let base = Some(Operand::BaseOffset(rs1, imm as i16));
+        let target = match self.opcode {
+            Opcode::JAL => self.operand(&OperandSpec::Imm20J),
+            _ => self.operand(&OperandSpec::Imm12B),
+        };
+
+        let pseudo = match self.opcode {
+            Opcode::JALR if rd == 0 && rs1 == 1 && imm == 0 => ("ret", [None, None]),
+            Opcode::JALR if rd == 0 && imm == 0 => ("jr", [reg(rs1), None]),
+            Opcode::JALR if rd == 0 => ("jr", [base, None]),
+            Opcode::JALR if rd == 1 && imm == 0 => ("jalr", [reg(rs1), None]),
+            Opcode::JALR if rd == 1 => ("jalr", [base, None]),
+            Opcode::JALR if imm == 0 => ("jalr", [reg(rd), reg(rs1)]),
+            Opcode::JAL if rd == 0 => ("j", [target, None]),
+            Opcode::JAL if rd == 1 => ("jal", [target, None]),
+            Opcode::ADDI if rd == 0 && rs1 == 0 && imm == 0 => ("nop", [None, None]),
+            Opcode::ADDI if rs1 == 0 => ("li", [reg(rd), Some(Operand::Imm(imm))]),
+            Opcode::ADDI if imm == 0 => ("mv", [reg(rd), reg(rs1)]),
+            Opcode::ANDI if imm == 0xff => ("zext.b", [reg(rd), reg(rs1)]),
+            Opcode::XORI if imm == -1 => ("not", [reg(rd), reg(rs1)]),
+            Opcode::SLTIU if imm == 1 => ("seqz", [reg(rd), reg(rs1)]),
+            Opcode::SUB if rs1 == 0 => ("neg", [reg(rd), reg(rs2)]),
+            Opcode::SUBW if rs1 == 0 => ("negw", [reg(rd), reg(rs2)]),
+            Opcode::SLTU if rs1 == 0 => ("snez", [reg(rd), reg(rs2)]),
+            Opcode::SLT if rs2 == 0 => ("sltz", [reg(rd), reg(rs1)]),
+            Opcode::SLT if rs1 == 0 => ("sgtz", [reg(rd), reg(rs2)]),
+            Opcode::ADDIW if imm == 0 => ("sext.w", [reg(rd), reg(rs1)]),
+            Opcode::BEQ if rs2 == 0 => ("beqz", [reg(rs1), target]),
+            Opcode::BNE if rs2 == 0 => ("bnez", [reg(rs1), target]),
+            Opcode::BGE if rs1 == 0 => ("blez", [reg(rs2), target]),
+            Opcode::BGE if rs2 == 0 => ("bgez", [reg(rs1), target]),
+            Opcode::BLT if rs2 == 0 => ("bltz", [reg(rs1), target]),
+            Opcode::BLT if rs1 == 0 => ("bgtz", [reg(rs2), target]),
+            // fence iorw,iorw, with the fields reserved for future use zero
+            Opcode::FENCE if self.word == 0x0ff0_000f => ("fence", [None, None]),
+            // csrw cycle, zero
+            Opcode::CSRRW if self.word == 0xc000_1073 => ("unimp", [None, None]),
+            Opcode::CSRRS if rs1 == 0 && csr == CSR_CYCLE => ("rdcycle", [reg(rd), None]),
+            Opcode::CSRRS if rs1 == 0 && csr == CSR_TIME => ("rdtime", [reg(rd), None]),
+            Opcode::CSRRS if rs1 == 0 && csr == CSR_INSTRET => ("rdinstret", [reg(rd), None]),
+            Opcode::CSRRS if rs1 == 0 && csr == CSR_FCSR => ("frcsr", [reg(rd), None]),
+            Opcode::CSRRW if rd == 0 && csr == CSR_FCSR => ("fscsr", [reg(rs1), None]),
+            Opcode::CSRRW if csr == CSR_FCSR => ("fscsr", [reg(rd), reg(rs1)]),
+            Opcode::CSRRS if rs1 == 0 && csr == CSR_FRM => ("frrm", [reg(rd), None]),
+            Opcode::CSRRW if rd == 0 && csr == CSR_FRM => ("fsrm", [reg(rs1), None]),
+            Opcode::CSRRW if csr == CSR_FRM => ("fsrm", [reg(rd), reg(rs1)]),
+            Opcode::CSRRWI if rd == 0 && csr == CSR_FRM => ("fsrmi", [zimm, None]),
+            Opcode::CSRRWI if csr == CSR_FRM => ("fsrmi", [reg(rd), zimm]),
+            Opcode::CSRRS if rs1 == 0 && csr == CSR_FFLAGS => ("frflags", [reg(rd), None]),
+            Opcode::CSRRW if rd == 0 && csr == CSR_FFLAGS => ("fsflags", [reg(rs1), None]),
+            Opcode::CSRRW if csr == CSR_FFLAGS => ("fsflags", [reg(rd), reg(rs1)]),
+            Opcode::CSRRWI if rd == 0 && csr == CSR_FFLAGS => ("fsflagsi", [zimm, None]),
+            Opcode::CSRRWI if csr == CSR_FFLAGS => ("fsflagsi", [reg(rd), zimm]),
+            Opcode::CSRRS if rs1 == 0 => ("csrr", [reg(rd), csr_op]),
+            Opcode::CSRRW if rd == 0 => ("csrw", [csr_op, reg(rs1)]),
+            Opcode::CSRRS if rd == 0 => ("csrs", [csr_op, reg(rs1)]),
+            Opcode::CSRRC if rd == 0 => ("csrc", [csr_op, reg(rs1)]),
+            Opcode::CSRRWI if rd == 0 => ("csrwi", [csr_op, zimm]),
+            Opcode::CSRRSI if rd == 0 => ("csrsi", [csr_op, zimm]),
+            Opcode::CSRRCI if rd == 0 => ("csrci", [csr_op, zimm]),
+            Opcode::FSGNJ_S if rs1 == rs2 => ("fmv.s", [freg(rd), freg(rs1)]),
+            Opcode::FSGNJN_S if rs1 == rs2 => ("fneg.s", [freg(rd), freg(rs1)]),
+            Opcode::FSGNJX_S if rs1 == rs2 => ("fabs.s", [freg(rd), freg(rs1)]),
+            Opcode::FSGNJ_D if rs1 == rs2 => ("fmv.d", [freg(rd), freg(rs1)]),
+            Opcode::FSGNJN_D if rs1 == rs2 => ("fneg.d", [freg(rd), freg(rs1)]),
+            Opcode::FSGNJX_D if rs1 == rs2 => ("fabs.d", [freg(rd), freg(rs1)]),
+            _ => return None,
+        };
+        Some(pseudo)
+    }
+}
+
+/// The assembly syntax an [`Instruction`] is shown in.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// As GNU objdump shows it, with its pseudo-instructions and hexadecimal shift amounts.
+    #[default]
+    Objdump,
+    /// As llvm-objdump shows it, with LLVM's pseudo-instructions, decimal immediates and operands
+    /// separated by `, `.
+    Llvm,
+    /// As GNU objdump shows it with `-M no-aliases`: every instruction under its own mnemonic,
+    /// with all of its operands.
+    NoAliases,
+    /// As a GNU assembler `.insn` directive that spells out the instruction's encoding fields,
+    /// such as `.insn\tr OP, 0, 0x0, a0, a1, a2`, so that it reassembles to the same bits even
+    /// when the assembler does not know the instruction.
+    ///
+    /// Branch and jump targets are `.`-relative, or absolute with a `0x` prefix once the address
+    /// is known. Instructions longer than 64 bits are shown as `.half` data for each of their
+    /// parcels, as they are in the other syntaxes.
+    Insn,
+}
+
+/// How registers are named when an [`Instruction`] is shown.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum RegisterNames {
+    /// The ABI names, such as `sp`, `s0` and `fa0`.
+    #[default]
+    Abi,
+    /// The ABI names, but with `fp` for `s0`, the frame pointer.
+    AbiFp,
+    /// The architectural names `x0`-`x31` and `f0`-`f31`, as GNU objdump shows them with
+    /// `-M numeric`.
+    Numeric,
+}
+
+/// The radix immediates are shown in.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Radix {
+    /// The radix the [`Syntax`] uses: GNU objdump shows shift amounts and the immediates of
+    /// `lui` and `auipc` in hexadecimal and other immediates in decimal, llvm-objdump shows every
+    /// immediate in decimal.
+    #[default]
+    Syntax,
+    /// Every immediate in decimal.
+    Decimal,
+    /// Every immediate in hexadecimal, with a `-0x` prefix for negative values.
+    Hex,
+    /// Every immediate in decimal, followed by its hexadecimal value in parentheses. Offsets in
+    /// memory references such as `24(sp)` are shown in decimal only.
+    Both,
+}
+
+/// Whether immediates are shown as signed or unsigned values.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Signedness {
+    /// Sign-extended immediates are shown as negative values when their sign bit is set.
+    #[default]
+    Signed,
+    /// Sign-extended immediates are shown as the unsigned XLEN-bit values they extend to; see
+    /// [`DisplayOptions::with_xlen`].
+    Unsigned,
+}
+
+/// How the immediate of `lui` and `auipc` is shown.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum UpperImmediate {
+    /// The 20-bit field, as it is written in assembly.
+    #[default]
+    Field,
+    /// The value the instruction adds, the field shifted up by 12 bits.
+    Shifted,
+}
+
+/// How an [`Instruction`] is shown by [`Instruction::display_with`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DisplayOptions {
+    syntax: Syntax,
+    register_names: RegisterNames,
+    radix: Radix,
+    signedness: Signedness,
+    upper_immediate: UpperImmediate,
+    xlen: Xlen,
+}
+
+impl DisplayOptions {
+    /// Options for the GNU objdump syntax.
+    pub fn new() -> Self {
+        DisplayOptions::default()
+    }
+
+    /// Show instructions in `syntax`.
+    pub fn with_syntax(mut self, syntax: Syntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// The syntax instructions are shown in.
+    pub fn syntax(&self) -> Syntax {
+        self.syntax
+    }
+
+    /// Name integer and floating-point registers as `register_names` describes.
+    pub fn with_register_names(mut self, register_names: RegisterNames) -> Self {
+        self.register_names = register_names;
+        self
+    }
+
+    /// How registers are named.
+    pub fn register_names(&self) -> RegisterNames {
+        self.register_names
+    }
+
+    /// Show immediates in `radix`.
+    pub fn with_radix(mut self, radix: Radix) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    /// The radix immediates are shown in.
+    pub fn radix(&self) -> Radix {
+        self.radix
+    }
+
+    /// Show sign-extended immediates as `signedness` describes.
+    pub fn with_signedness(mut self, signedness: Signedness) -> Self {
+        self.signedness = signedness;
+        self
+    }
+
+    /// Whether immediates are shown as signed or unsigned values.
+    pub fn signedness(&self) -> Signedness {
+        self.signedness
+    }
+
+    /// Show unsigned immediates and absolute addresses at the width of `xlen`, normally the
+    /// [`RiscVDecoder::xlen`] of the decoder the instruction came from.
+    ///
+    /// [`RiscVDecoder::xlen`]: crate::RiscVDecoder::xlen
+    pub fn with_xlen(mut self, xlen: Xlen) -> Self {
+        self.xlen = xlen;
+        self
+    }
+
+    /// The register width unsigned immediates and absolute addresses are shown at.
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
+
+    /// Show the immediate of `lui` and `auipc` as `upper_immediate` describes.
+    pub fn with_upper_immediate(mut self, upper_immediate: UpperImmediate) -> Self {
+        self.upper_immediate = upper_immediate;
+        self
+    }
+
+    /// How the immediate of `lui` and `auipc` is shown.
+    pub fn upper_immediate(&self) -> UpperImmediate {
+        self.upper_immediate
+    }
+
+    /// `address`, wrapped to the register width.
+    fn address(&self, address: u64) -> u64 {
+        match self.xlen {
+            Xlen::X32 => address as u32 as u64,
+            Xlen::X64 => address,
+        }
+    }
+
+    fn gpr(&self, reg: u8) -> RegName {
+        RegName {
+            reg,
+            float: false,
+            names: self.register_names,
+        }
+    }
+
+    fn fpr(&self, reg: u8) -> RegName {
+        RegName {
+            reg,
+            float: true,
+            names: self.register_names,
+        }
+    }
+
+    fn separator(&self) -> &'static str {
+        match self.syntax {
+            Syntax::Llvm | Syntax::Insn => ", ",
+            Syntax::Objdump | Syntax::NoAliases => ",",
+        }
+    }
+
+    /// `value`, an immediate GNU objdump shows in hexadecimal if `objdump_hex`.
+    fn imm(&self, value: i64, objdump_hex: bool) -> Imm {
+        let radix = match self.radix {
+            Radix::Syntax if objdump_hex && self.syntax != Syntax::Llvm => Radix::Hex,
+            Radix::Syntax => Radix::Decimal,
+            radix => radix,
+        };
+        Imm {
+            value,
+            radix,
+            unsigned: false,
+        }
+    }
+
+    /// `value`, a sign-extended immediate.
+    fn signed_imm(&self, value: i32, objdump_hex: bool) -> Imm {
+        match (self.signedness, self.xlen) {
+            (Signedness::Signed, _) => self.imm(value as i64, objdump_hex),
+            (Signedness::Unsigned, Xlen::X32) => self.imm(value as u32 as i64, objdump_hex),
+            (Signedness::Unsigned, Xlen::X64) => Imm {
+                unsigned: true,
+                ..self.imm(value as i64, objdump_hex)
+            },
+        }
+    }
+
+    /// `value`, the sign-extended offset of a memory reference, which is kept to one number.
+    fn offset_imm(&self, value: i32) -> Imm {
+        let mut imm = self.signed_imm(value, false);
+        if imm.radix == Radix::Both {
+            imm.radix = Radix::Decimal;
+        }
+        imm
+    }
+}
+
+/// An immediate, in a radix other than [`Radix::Syntax`].
+struct Imm {
+    value: i64,
+    radix: Radix,
+    /// Whether `value` is shown as the unsigned 64-bit value of its bits.
+    unsigned: bool,
+}
+
+impl fmt::Display for Imm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let decimal = |f: &mut fmt::Formatter| match self.unsigned {
+            true => write!(f, "{}", self.value as u64),
+            false => write!(f, "{}", self.value),
+        };
+        let hex = |f: &mut fmt::Formatter| {
+            if self.value < 0 && !self.unsigned {
+                write!(f, "-{:#x}", self.value.unsigned_abs())
+            } else {
+                write!(f, "{:#x}", self.value as u64)
+            }
+        };
+        match self.radix {
+            Radix::Hex => hex(f),
+            Radix::Both => {
+                decimal(f)?;
+                f.write_str(" (")?;
+                hex(f)?;
+                f.write_str(")")
+            }
+            Radix::Syntax | Radix::Decimal => decimal(f),
+        }
+    }
+}
+
+/// An integer or floating-point register, named as a [`RegisterNames`] describes.
+struct RegName {
+    reg: u8,
+    float: bool,
+    names: RegisterNames,
+}
+
+impl fmt::Display for RegName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.names, self.float) {
+            (RegisterNames::Numeric, false) => write!(f, "x{}", self.reg),
+            (RegisterNames::Numeric, true) => write!(f, "f{}", self.reg),
+            (RegisterNames::AbiFp, false) if self.reg == 8 => f.write_str("fp"),
+            (_, false) => f.write_str(REG_NAMES[self.reg as usize]),
+            (_, true) => f.write_str(FREG_NAMES[self.reg as usize]),
+        }
+    }
+}
+
+/// Names for addresses, used to show branch targets and PC-relative references symbolically.
+pub trait Symbolizer {
+    /// The name of the symbol `address` falls in, and the offset of `address` from its start.
+    fn symbolize(&self, address: u64) -> Option<(&str, u64)>;
+}
+
+/// An [`Instruction`] shown with a choice of [`DisplayOptions`].
+#[derive(Copy, Clone)]
+pub struct InstructionDisplay<'a> {
+    instruction: &'a Instruction,
+    options: DisplayOptions,
+    address: Option<u64>,
+    symbolizer: Option<&'a dyn Symbolizer>,
+    previous: Option<&'a Instruction>,
+}
+
+impl fmt::Debug for InstructionDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InstructionDisplay")
+            .field("instruction", &self.instruction)
+            .field("options", &self.options)
+            .field("address", &self.address)
+            .field("symbolizer", &self.symbolizer.is_some())
+            .field("previous", &self.previous)
+            .finish()
+    }
+}
+
+impl Instruction {
+    /// Show this instruction as `options` describe, rather than as its `Display` impl does.
+    pub fn display_with(&self, options: DisplayOptions) -> InstructionDisplay<'_> {
+        InstructionDisplay {
+            instruction: self,
+            options,
+            address: None,
+            symbolizer: None,
+            previous: None,
+        }
+    }
+}
+
+impl<'a> InstructionDisplay<'a> {
+    /// Show the instruction as if it were at `address`, with the absolute address of branch and
+    /// jump targets.
+    ///
+    /// GNU objdump shows these addresses in hexadecimal without a `0x` prefix, llvm-objdump with
+    /// one. Addresses wrap at the [`DisplayOptions::xlen`]. Without a known address, targets are
+    /// shown relative to the instruction, as in `bnez\ta5,$-0x14`.
+    pub fn at(mut self, address: u64) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Follow the absolute addresses of branch and jump targets with the symbol `symbolizer`
+    /// names for them, as in `jal\t10350 <memcpy>`.
+    ///
+    /// Has no effect unless the instruction's address is known; see [`InstructionDisplay::at`].
+    pub fn with_symbolizer(mut self, symbolizer: &'a dyn Symbolizer) -> Self {
+        self.symbolizer = Some(symbolizer);
+        self
+    }
+
+    /// Show the instruction as following `previous`, the instruction just before it.
+    ///
+    /// When `previous` is an `auipc` and this instruction adds an immediate to its result, as
+    /// `addi`, `jalr`, loads and stores do, the address they form together is shown in a comment,
+    /// as in `addi\ta0,a0,-16 # 12340 <global_var>`. Has no effect unless the instruction's
+    /// address is known; see [`InstructionDisplay::at`].
+    pub fn after(mut self, previous: &'a Instruction) -> Self {
+        self.previous = Some(previous);
+        self
+    }
+
+    fn render_address<S: DisplaySink + ?Sized>(&self, sink: &mut S, address: u64) -> fmt::Result {
+        let address = self.options.address(address);
+        write_address(sink, address, self.options.syntax)?;
+        let (name, offset) = match self
+            .symbolizer
+            .and_then(|symbolizer| symbolizer.symbolize(address))
+        {
+            Some(symbol) => symbol,
+            None => return Ok(()),
+        };
+        sink.token(TokenKind::Text, &" <")?;
+        sink.token(TokenKind::Symbol, &name)?;
+        if offset != 0 {
+            sink.token(TokenKind::Text, &format_args!("+{:#x}", offset))?;
+        }
+        sink.token(TokenKind::Text, &">")
+    }
+
+    /// The address formed by this instruction at `address` from the result of an `auipc` just
+    /// before it.
+    fn pc_relative_reference(&self, address: u64) -> Option<u64> {
+        let instr = self.instruction;
+        let auipc = self.previous.filter(|prev| prev.opcode == Opcode::AUIPC)?;
+        let base = auipc.field(FieldSpec::Rd);
+        if base == 0 || instr.field(FieldSpec::Rs1) != base {
+            return None;
+        }
+        let offset = match instr.opcode {
+            Opcode::ADDI
+            | Opcode::JALR
+            | Opcode::LB
+            | Opcode::LH
+            | Opcode::LW
+            | Opcode::LD
+            | Opcode::LBU
+            | Opcode::LHU
+            | Opcode::LWU => instr.field(FieldSpec::Imm12I),
+            Opcode::SB | Opcode::SH | Opcode::SW | Opcode::SD => instr.field(FieldSpec::Imm12S),
+            _ => return None,
+        };
+        let auipc_address = address.wrapping_sub(auipc.length as u64);
+        Some(
+            auipc_address
+                .wrapping_add(auipc.field(FieldSpec::Imm20U) as i32 as i64 as u64)
+                .wrapping_add(offset as i32 as i64 as u64),
+        )
+    }
+}
+
+/// What an [`Instruction`] is shown with by its [`ShowContextual`] impl: the
+/// [`DisplayOptions`], and a [`Symbolizer`] to name branch and jump targets.
+#[derive(Copy, Clone, Default)]
+pub struct DisplayContext<'a> {
+    options: DisplayOptions,
+    symbolizer: Option<&'a dyn Symbolizer>,
+}
+
+impl fmt::Debug for DisplayContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DisplayContext")
+            .field("options", &self.options)
+            .field("symbolizer", &self.symbolizer.is_some())
+            .finish()
+    }
+}
+
+impl<'a> DisplayContext<'a> {
+    /// The default options, and no symbolizer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show instructions as `options` describe.
+    pub fn with_options(mut self, options: DisplayOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The options instructions are shown with.
+    pub fn options(&self) -> DisplayOptions {
+        self.options
+    }
+
+    /// Follow branch and jump targets with the symbol `symbolizer` names for them.
+    pub fn with_symbolizer(mut self, symbolizer: &'a dyn Symbolizer) -> Self {
+        self.symbolizer = Some(symbolizer);
+        self
+    }
+
+    /// The symbolizer naming branch and jump targets, if any.
+    pub fn symbolizer(&self) -> Option<&'a dyn Symbolizer> {
+        self.symbolizer
+    }
+
+    /// Show `instruction`, at `address`, as this context describes.
+    pub(crate) fn display_at<'i>(
+        &self,
+        instruction: &'i Instruction,
+        address: u64,
+    ) -> InstructionDisplay<'i>
+    where
+        'a: 'i,
+    {
+        let display = instruction.display_with(self.options).at(address);
+        match self.symbolizer {
+            Some(symbolizer) => display.with_symbolizer(symbolizer),
+            None => display,
+        }
+    }
+}
+
+/// Instructions at a known address are shown with the absolute address of their branch and jump
+/// targets, as the context, or [`DisplayContext::default`] without one, describes.
+impl<'a, T: fmt::Write, Y: YaxColors> ShowContextual<u64, DisplayContext<'a>, T, Y>
+    for Instruction
+{
+    fn contextualize(
+        &self,
+        colors: &Y,
+        address: u64,
+        context: Option<&DisplayContext<'a>>,
+        out: &mut T,
+    ) -> fmt::Result {
+        context
+            .copied()
+            .unwrap_or_default()
+            .display_at(self, address)
+            .colorize(colors, out)
+    }
+}
+
+/// Instructions are shown as GNU objdump shows them, with the standard pseudo-instructions.
+///
+/// Branch and jump targets are shown relative to the instruction, as in `j\t$+0x10`; see
+/// [`InstructionDisplay::at`] for instructions at a known address. An
+/// instruction that did not decode is shown as the data it was read from, with `.half` or
+/// `.word`.
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.opcode == Opcode::ADDI {
-            match (
-                self.operand(&self.operands[0]),
-                self.operand(&self.operands[1]),
-                self.operand(&self.operands[2]),
-            ) {
-                (Some(a), Some(b), Some(Operand::Imm(0))) => {
-                    if a == b {
-                        return write!(f, "nop");
-                    } else {
-                        return write!(f, "mv {}, {}", a, b);
-                    }
-                }
-                _ => {}
-            }
-        } else if self.opcode == Opcode::BEQ {
-            match (
-                self.operand(&self.operands[0]),
-                self.operand(&self.operands[1]),
-                self.operand(&self.operands[2]),
-            ) {
-                (Some(Operand::Reg(a)), Some(Operand::Reg(b)), Some(Operand::Imm(offs))) => {
-                    if a == b {
-                        if offs < 0 {
-                            return write!(f, "beq $-{:#x}", offs.wrapping_neg());
-                        } else {
-                            return write!(f, "beq $+{:#x}", offs);
-                        }
-                    }
-                }
-                _ => {}
-            }
-        } else if self.opcode == Opcode::SLL {
-            match (
-                self.operand(&self.operands[0]),
-                self.operand(&self.operands[1]),
-                self.operand(&self.operands[2]),
-            ) {
-                (Some(Operand::Reg(dest)), Some(Operand::Reg(src)), Some(Operand::Shift(0))) => {
-                    // TODO: should this also test for dest == `zero`?
-                    if dest == src {
-                        return write!(f, "nop");
-                    }
+        self.colorize(&NoColors, f)
+    }
+}
+
+impl<T: fmt::Write, Y: YaxColors> Colorize<T, Y> for Instruction {
+    fn colorize(&self, colors: &Y, out: &mut T) -> fmt::Result {
+        self.display_with(DisplayOptions::default())
+            .colorize(colors, out)
+    }
+}
+
+impl fmt::Display for InstructionDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.colorize(&NoColors, f)
+    }
+}
+
+/// Mnemonics are styled by what the instruction does: control flow, memory accesses,
+/// comparisons and platform operations such as fences and CSR accesses are told apart from
+/// arithmetic. CSR names are styled as platform operations.
+///
+/// Terminal colors need the `colors` feature of `yaxpeax-arch`.
+impl<T: fmt::Write, Y: YaxColors> Colorize<T, Y> for InstructionDisplay<'_> {
+    fn colorize(&self, colors: &Y, out: &mut T) -> fmt::Result {
+        self.write_to(&mut ColorSink {
+            palette: Colors(colors),
+            out,
+        })
+    }
+}
+
+/// A register named by a [`Token`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Register {
+    /// Integer register `x0`-`x31`
+    X(u8),
+    /// Floating-point register `f0`-`f31`
+    F(u8),
+    /// Vector register `v0`-`v31`
+    V(u8),
+}
+
+/// What a [`Token`] of a shown instruction is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    /// The mnemonic of the instruction or of the pseudo-instruction shown for it.
+    Mnemonic,
+    /// A register.
+    Register(Register),
+    /// An immediate, with the value shown.
+    Immediate(i64),
+    /// The address of a branch or jump target or of a PC-relative reference.
+    Address(u64),
+    /// The name of the symbol an address falls in.
+    Symbol,
+    /// A control and status register, by name or number.
+    Csr(u16),
+    /// Anything else: separators, parentheses, rounding modes, fence sets and comments.
+    Text,
+}
+
+/// A piece of a shown instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// What the piece is.
+    pub kind: TokenKind,
+    /// Where the piece is in the shown text, in bytes.
+    pub span: Range<usize>,
+    /// Whether the piece is part of a memory reference, such as `8(sp)` or `(a1),4`.
+    pub memory: bool,
+}
+
+impl InstructionDisplay<'_> {
+    /// The text of the instruction, as its `Display` impl shows it, and the pieces it is made of,
+    /// in order.
+    pub fn tokens(&self) -> (String, Vec<Token>) {
+        let mut collector = TokenCollector {
+            text: String::new(),
+            tokens: Vec::new(),
+            memory: false,
+        };
+        self.write_to(&mut collector)
+            .expect("writing to a String does not fail");
+        (collector.text, collector.tokens)
+    }
+
+    /// Write the instruction, as its `Display` impl shows it, to `sink`, piece by piece.
+    pub fn write_to<S: DisplaySink + ?Sized>(&self, sink: &mut S) -> fmt::Result {
+        let instr = self.instruction;
+        let options = &self.options;
+        if options.syntax == Syntax::Insn {
+            return self.write_insn(sink);
+        }
+        if instr.opcode == Opcode::Invalid {
+            // shown as data, like GNU objdump shows what it cannot decode
+            let (directive, value) = match instr.length {
+                2 => (".half", instr.word & 0xffff),
+                _ => (".word", instr.word),
+            };
+            sink.mnemonic(&instr.opcode, Some(directive))?;
+            sink.token(TokenKind::Text, &"\t")?;
+            return sink.token(
+                TokenKind::Immediate(value as i64),
+                &format_args!("{:#x}", value),
+            );
+        }
+        if instr.opcode == Opcode::Opaque {
+            if instr.length > 8 {
+                return self.write_parcels(sink);
+            }
+            sink.mnemonic(&instr.opcode, None)?;
+            sink.token(TokenKind::Text, &"\t")?;
+            sink.token(TokenKind::Immediate(instr.length as i64), &instr.length)?;
+            sink.token(TokenKind::Text, &", ")?;
+            let word = instr.long_word();
+            return sink.token(
+                TokenKind::Immediate(word as i64),
+                &format_args!("{:#x}", word),
+            );
+        }
+
+        let mut operands = [None; 5];
+        let pseudo = match options.syntax {
+            Syntax::Objdump => instr.pseudo(),
+            // LLVM has no alias for zero-extending a byte with andi
+            Syntax::Llvm if instr.opcode == Opcode::ANDI => None,
+            Syntax::Llvm => instr.pseudo(),
+            Syntax::NoAliases | Syntax::Insn => None,
+        };
+        match pseudo {
+            Some((mnemonic, pseudo_operands)) => {
+                sink.mnemonic(&instr.opcode, Some(mnemonic))?;
+                operands[..2].copy_from_slice(&pseudo_operands);
+            }
+            None => {
+                sink.mnemonic(&instr.opcode, None)?;
+                for (slot, op) in operands.iter_mut().zip(instr.operands.iter()) {
+                    *slot = instr.operand(op);
                 }
-                _ => {}
             }
         }
 
-        fn display_operand(f: &mut fmt::Formatter, opcode: &Opcode, op: &Operand) -> fmt::Result {
-            if *opcode == Opcode::LUI || *opcode == Opcode::AUIPC {
-                // we show the immediate of LUI as an unsigned integer, because the docs say so.
-                if let Operand::Imm(imm) = op {
-                    return write!(f, "{:#x}", (*imm as u32) >> 12);
+        for (i, op) in operands.iter().map_while(|op| op.as_ref()).enumerate() {
+            let separator = if i == 0 { "\t" } else { options.separator() };
+            sink.token(TokenKind::Text, &separator)?;
+            match (&instr.opcode, op) {
+                (Opcode::LUI | Opcode::AUIPC, Operand::Imm(imm)) => {
+                    let imm = match options.upper_immediate {
+                        // shown shifted down, as it is written in assembly
+                        UpperImmediate::Field => options.imm(((*imm as u32) >> 12) as i64, true),
+                        UpperImmediate::Shifted => options.signed_imm(*imm, true),
+                    };
+                    sink.token(TokenKind::Immediate(imm.value), &imm)?
                 }
-            } else if let Operand::Imm(imm) = op {
-                if *imm < 0 {
-                    return write!(f, "-{:#x}", imm.wrapping_neg());
-                } else {
-                    return write!(f, "{:#x}", imm);
+                (_, Operand::JOffset(offs)) if self.address.is_some() => {
+                    let address = self.address.unwrap_or_default();
+                    self.render_address(sink, address.wrapping_add(*offs as i64 as u64))?
                 }
+                _ => render_operand(sink, op, options)?,
             }
+        }
 
-            write!(f, "{}", op)
+        if let Some(reference) = self.address.and_then(|a| self.pc_relative_reference(a)) {
+            sink.token(TokenKind::Text, &" # ")?;
+            self.render_address(sink, reference)?;
         }
-        write!(f, "{}", self.opcode)?;
 
-        let mut wrote_operand = false;
-        for op in self.operands.iter() {
-            match self.operand(op) {
-                Some(op) => {
-                    if wrote_operand {
-                        write!(f, ", ")?;
-                    } else {
-                        write!(f, " ")?;
-                        wrote_operand = true;
-                    }
-                    display_operand(f, &self.opcode, &op)?;
+        Ok(())
+    }
+
+    /// Write the parcels of an instruction longer than 64 bits, too long for its value to be
+    /// written as one number, as `.half` data.
+    fn write_parcels<S: DisplaySink + ?Sized>(&self, sink: &mut S) -> fmt::Result {
+        let instr = self.instruction;
+        sink.mnemonic(&instr.opcode, Some(".half"))?;
+        let mut index = 0;
+        while let Some(parcel) = instr.parcel(index) {
+            sink.token(TokenKind::Text, &if index == 0 { "\t" } else { ", " })?;
+            sink.token(
+                TokenKind::Immediate(parcel as i64),
+                &format_args!("{:#x}", parcel),
+            )?;
+            index += 1;
+        }
+        Ok(())
+    }
+
+    /// Write the instruction as an `.insn` directive with the format its major opcode implies, or
+    /// as its raw bits for compressed, long and unclassified encodings.
+    fn write_insn<S: DisplaySink + ?Sized>(&self, sink: &mut S) -> fmt::Result {
+        let instr = self.instruction;
+        let options = &self.options;
+        let word = instr.word;
+        let field = |shift: u32, bits: u32| (word >> shift) & ((1 << bits) - 1);
+        let format = match instr.length {
+            4 => insn_format(field(2, 5)),
+            _ => None,
+        };
+        let (format, opcode) = match format {
+            Some(format) => format,
+            None => {
+                let length = instr.length;
+                if crate::instruction_length(word as u16) != Some(length) {
+                    // a truncated parcel can not be reassembled with its length; show it as data
+                    sink.mnemonic(&instr.opcode, Some(".half"))?;
+                    sink.token(TokenKind::Text, &"\t")?;
+                    let value = word & 0xffff;
+                    return sink.token(
+                        TokenKind::Immediate(value as i64),
+                        &format_args!("{:#x}", value),
+                    );
                 }
-                _ => {
-                    return Ok(());
+                if length > 8 {
+                    return self.write_parcels(sink);
                 }
+                sink.mnemonic(&instr.opcode, Some(".insn"))?;
+                sink.token(TokenKind::Text, &"\t")?;
+                sink.token(TokenKind::Immediate(length as i64), &length)?;
+                sink.token(TokenKind::Text, &", ")?;
+                let value = match length {
+                    2 => (word & 0xffff) as u64,
+                    _ => instr.long_word(),
+                };
+                return sink.token(
+                    TokenKind::Immediate(value as i64),
+                    &format_args!("{:#x}", value),
+                );
+            }
+        };
+
+        sink.mnemonic(&instr.opcode, Some(".insn"))?;
+        sink.token(TokenKind::Text, &format_args!("\t{} ", format))?;
+        sink.token(TokenKind::Text, &opcode)?;
+        let reg = |sink: &mut S, shift: u32| {
+            let reg = field(shift, 5) as u8;
+            sink.token(TokenKind::Text, &", ")?;
+            match insn_register(word, shift) {
+                Some(Register::F(_)) => {
+                    sink.token(TokenKind::Register(Register::F(reg)), &options.fpr(reg))
+                }
+                Some(_) => sink.token(TokenKind::Register(Register::X(reg)), &options.gpr(reg)),
+                None => sink.token(TokenKind::Text, &options.gpr(reg)),
+            }
+        };
+        let number = |sink: &mut S, value: i64, hex: bool| {
+            sink.token(TokenKind::Text, &", ")?;
+            match hex {
+                true => sink.token(TokenKind::Immediate(value), &format_args!("{:#x}", value)),
+                false => sink.token(TokenKind::Immediate(value), &value),
+            }
+        };
+        let target = |sink: &mut S, offset: i32| {
+            sink.token(TokenKind::Text, &", ")?;
+            match self.address {
+                Some(address) => {
+                    let address = options.address(address.wrapping_add(offset as i64 as u64));
+                    sink.token(TokenKind::Address(address), &format_args!("{:#x}", address))
+                }
+                None => sink.token(
+                    TokenKind::Immediate(offset as i64),
+                    &format_args!(".{:+}", offset),
+                ),
+            }
+        };
+        let funct3 = field(12, 3) as i64;
+        match format {
+            "r" => {
+                number(sink, funct3, false)?;
+                number(sink, field(25, 7) as i64, true)?;
+                reg(sink, 7)?;
+                reg(sink, 15)?;
+                reg(sink, 20)?;
+            }
+            "r4" => {
+                number(sink, funct3, false)?;
+                number(sink, field(25, 2) as i64, true)?;
+                reg(sink, 7)?;
+                reg(sink, 15)?;
+                reg(sink, 20)?;
+                reg(sink, 27)?;
             }
+            "i" => {
+                number(sink, funct3, false)?;
+                reg(sink, 7)?;
+                reg(sink, 15)?;
+                number(sink, (word as i32 >> 20) as i64, false)?;
+            }
+            "s" => {
+                number(sink, funct3, false)?;
+                reg(sink, 20)?;
+                let imm = ((word as i32 >> 25) << 5) | field(7, 5) as i32;
+                sink.token(TokenKind::Text, &", ")?;
+                sink.memory(true);
+                sink.token(TokenKind::Immediate(imm as i64), &imm)?;
+                sink.token(TokenKind::Text, &"(")?;
+                let base = field(15, 5) as u8;
+                sink.token(TokenKind::Register(Register::X(base)), &options.gpr(base))?;
+                sink.token(TokenKind::Text, &")")?;
+                sink.memory(false);
+            }
+            "b" => {
+                number(sink, funct3, false)?;
+                reg(sink, 15)?;
+                reg(sink, 20)?;
+                let offset = ((word as i32 >> 31) << 12)
+                    | (field(7, 1) << 11) as i32
+                    | (field(25, 6) << 5) as i32
+                    | (field(8, 4) << 1) as i32;
+                target(sink, offset)?;
+            }
+            "u" => {
+                reg(sink, 7)?;
+                number(sink, field(12, 20) as i64, true)?;
+            }
+            _ => {
+                reg(sink, 7)?;
+                let offset = ((word as i32 >> 31) << 20)
+                    | (field(12, 8) << 12) as i32
+                    | (field(20, 1) << 11) as i32
+                    | (field(21, 10) << 1) as i32;
+                target(sink, offset)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write the absolute `address` of a branch or jump target, in hexadecimal with a `0x` prefix
+/// for LLVM and without one for GNU objdump.
+fn write_address<S: DisplaySink + ?Sized>(
+    sink: &mut S,
+    address: u64,
+    syntax: Syntax,
+) -> fmt::Result {
+    let kind = TokenKind::Address(address);
+    match syntax {
+        Syntax::Llvm => sink.token(kind, &format_args!("{:#x}", address)),
+        Syntax::Objdump | Syntax::NoAliases | Syntax::Insn => {
+            sink.token(kind, &format_args!("{:x}", address))
+        }
+    }
+}
+
+/// The register in the 5-bit field at `shift` of the 32-bit instruction `word`, as an `.insn`
+/// directive shows it, or `None` for the vector and immediate fields of vector instructions,
+/// which are not told apart.
+fn insn_register(word: u32, shift: u32) -> Option<Register> {
+    let field = |shift: u32, bits: u32| (word >> shift) & ((1 << bits) - 1);
+    let reg = field(shift, 5) as u8;
+    let float = match field(2, 5) {
+        0x01 | 0x09 if shift == 15 => false,
+        // scalar loads and stores have a width of 1 to 4; the others are vector ones
+        0x01 | 0x09 => match field(12, 3) {
+            1..=4 => true,
+            _ => return None,
+        },
+        0x10..=0x13 => true,
+        // conversions, moves and compares between integer and floating-point registers
+        0x14 => !matches!(
+            (field(27, 5), shift),
+            (0b10100 | 0b11000 | 0b11100, 7) | (0b11010 | 0b11110, 15)
+        ),
+        0x15 => return None,
+        _ => false,
+    };
+    Some(match float {
+        true => Register::F(reg),
+        false => Register::X(reg),
+    })
+}
+
+/// The `.insn` format and GNU assembler name of the 32-bit major opcode `major`, bits 6:2 of
+/// the instruction, or `None` for the reserved ones.
+fn insn_format(major: u32) -> Option<(&'static str, &'static str)> {
+    Some(match major {
+        0x00 => ("i", "LOAD"),
+        0x01 => ("i", "LOAD_FP"),
+        0x02 => ("r", "CUSTOM_0"),
+        0x03 => ("i", "MISC_MEM"),
+        0x04 => ("i", "OP_IMM"),
+        0x05 => ("u", "AUIPC"),
+        0x06 => ("i", "OP_IMM_32"),
+        0x08 => ("s", "STORE"),
+        0x09 => ("s", "STORE_FP"),
+        0x0a => ("r", "CUSTOM_1"),
+        0x0b => ("r", "AMO"),
+        0x0c => ("r", "OP"),
+        0x0d => ("u", "LUI"),
+        0x0e => ("r", "OP_32"),
+        0x10 => ("r4", "MADD"),
+        0x11 => ("r4", "MSUB"),
+        0x12 => ("r4", "NMSUB"),
+        0x13 => ("r4", "NMADD"),
+        0x14 => ("r", "OP_FP"),
+        0x15 => ("r", "OP_V"),
+        0x16 => ("r", "CUSTOM_2"),
+        0x18 => ("b", "BRANCH"),
+        0x19 => ("i", "JALR"),
+        0x1b => ("j", "JAL"),
+        0x1c => ("i", "SYSTEM"),
+        0x1e => ("r", "CUSTOM_3"),
+        _ => return None,
+    })
+}
+
+/// Where the pieces of a shown instruction go, as [`InstructionDisplay::write_to`] writes them.
+///
+/// Nothing on the way to a sink allocates: a sink that does not allocate itself, such as a
+/// [`BufferSink`] or a `String` with room to spare, shows instructions without touching the heap.
+pub trait DisplaySink {
+    /// Write `text`, a piece of kind `kind`.
+    fn token(&mut self, kind: TokenKind, text: &dyn fmt::Display) -> fmt::Result;
+
+    /// Write the mnemonic of an instruction with opcode `opcode`, or of `pseudo`, a
+    /// pseudo-instruction standing for it.
+    fn mnemonic(&mut self, opcode: &Opcode, pseudo: Option<&'static str>) -> fmt::Result {
+        match pseudo {
+            Some(pseudo) => self.token(TokenKind::Mnemonic, &pseudo),
+            None => self.token(TokenKind::Mnemonic, opcode),
+        }
+    }
+
+    /// Pieces from here on are part of a memory reference if `memory`.
+    fn memory(&mut self, _memory: bool) {}
+}
+
+impl DisplaySink for String {
+    fn token(&mut self, _kind: TokenKind, text: &dyn fmt::Display) -> fmt::Result {
+        use fmt::Write;
+
+        write!(self, "{}", text)
+    }
+}
+
+/// A sink writing into a fixed buffer.
+///
+/// A piece that does not fit is an error, and leaves the buffer holding the pieces written
+/// before it, none of that piece.
+#[derive(Debug)]
+pub struct BufferSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> BufferSink<'a> {
+    /// A sink writing into `buf`, from its start.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        BufferSink { buf, len: 0 }
+    }
+
+    /// The text written so far.
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.buf[..self.len]).expect("only whole strs are written")
+    }
+
+    /// The number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forget the text written so far.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl fmt::Write for BufferSink<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl DisplaySink for BufferSink<'_> {
+    fn token(&mut self, _kind: TokenKind, text: &dyn fmt::Display) -> fmt::Result {
+        use fmt::Write;
+
+        // a piece can be written in several parts, of which only the first may fit
+        let len = self.len;
+        write!(self, "{}", text).inspect_err(|_| self.len = len)
+    }
+}
+
+/// A sink writing pieces to `out` in the [`Style`] a [`Palette`] gives each kind of piece, as
+/// [`Colorize`] writes them with the colors of a [`YaxColors`].
+pub struct ColorSink<'a, T, P> {
+    palette: P,
+    out: &'a mut T,
+}
+
+impl<'a, T: fmt::Write, P: Palette> ColorSink<'a, T, P> {
+    /// A sink painting pieces with `palette` into `out`.
+    pub fn new(palette: P, out: &'a mut T) -> Self {
+        ColorSink { palette, out }
+    }
+}
+
+impl<T: fmt::Write, P: Palette> DisplaySink for ColorSink<'_, T, P> {
+    fn token(&mut self, kind: TokenKind, text: &dyn fmt::Display) -> fmt::Result {
+        let style = match kind {
+            TokenKind::Mnemonic => Style::Arithmetic,
+            TokenKind::Register(_) => Style::Register,
+            TokenKind::Immediate(_) => Style::Number,
+            TokenKind::Address(_) => Style::Address,
+            TokenKind::Symbol => Style::Symbol,
+            TokenKind::Csr(_) => Style::Platform,
+            TokenKind::Text => return write!(self.out, "{}", text),
+        };
+        self.palette.paint(style, text, self.out)
+    }
+
+    fn mnemonic(&mut self, opcode: &Opcode, pseudo: Option<&'static str>) -> fmt::Result {
+        let style = match pseudo {
+            Some("nop") => Style::Nop,
+            Some("unimp") => Style::Stop,
+            _ => mnemonic_style(opcode),
+        };
+        match pseudo {
+            Some(pseudo) => self.palette.paint(style, &pseudo, self.out),
+            None => self.palette.paint(style, opcode, self.out),
+        }
+    }
+}
+
+/// What a [`ColorSink`] styles a piece of an instruction as, one for each [`YaxColors`] method
+/// it uses.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Style {
+    /// The mnemonic of an arithmetic or otherwise unclassified instruction: `arithmetic_op`
+    Arithmetic,
+    /// The mnemonic of `nop` or `pause`: `nop_op`
+    Nop,
+    /// The mnemonic of `unimp` or `ebreak`: `stop_op`
+    Stop,
+    /// The mnemonic of a branch, jump or return: `control_flow_op`
+    ControlFlow,
+    /// The mnemonic of a load or store: `data_op`
+    Data,
+    /// The mnemonic of a comparison: `comparison_op`
+    Comparison,
+    /// The mnemonic of an instruction that did not decode: `invalid_op`
+    Invalid,
+    /// The mnemonic of a system, CSR, fence or cache instruction, or a CSR: `platform_op`
+    Platform,
+    /// The mnemonic of an instruction from a [`CustomDecoder`]: `misc_op`
+    ///
+    /// [`CustomDecoder`]: crate::CustomDecoder
+    Misc,
+    /// A register: `register`
+    Register,
+    /// An immediate: `number`
+    Number,
+    /// An absolute address: `address`
+    Address,
+    /// The symbol naming an address: `symbol`
+    Symbol,
+}
+
+/// Writes pieces of an instruction in a [`Style`].
+pub trait Palette {
+    /// Write `text` to `out` in `style`.
+    fn paint(&self, style: Style, text: &dyn fmt::Display, out: &mut dyn fmt::Write)
+        -> fmt::Result;
+}
+
+impl<P: Palette + ?Sized> Palette for &P {
+    fn paint(
+        &self,
+        style: Style,
+        text: &dyn fmt::Display,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        (**self).paint(style, text, out)
+    }
+}
+
+/// The palette of a [`YaxColors`].
+struct Colors<'a, Y>(&'a Y);
+
+impl<Y: YaxColors> Palette for Colors<'_, Y> {
+    fn paint(
+        &self,
+        style: Style,
+        text: &dyn fmt::Display,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let colors = self.0;
+        match style {
+            Style::Arithmetic => write!(out, "{}", colors.arithmetic_op(text)),
+            Style::Nop => write!(out, "{}", colors.nop_op(text)),
+            Style::Stop => write!(out, "{}", colors.stop_op(text)),
+            Style::ControlFlow => write!(out, "{}", colors.control_flow_op(text)),
+            Style::Data => write!(out, "{}", colors.data_op(text)),
+            Style::Comparison => write!(out, "{}", colors.comparison_op(text)),
+            Style::Invalid => write!(out, "{}", colors.invalid_op(text)),
+            Style::Platform => write!(out, "{}", colors.platform_op(text)),
+            Style::Misc => write!(out, "{}", colors.misc_op(text)),
+            Style::Register => write!(out, "{}", colors.register(text)),
+            Style::Number => write!(out, "{}", colors.number(text)),
+            Style::Address => write!(out, "{}", colors.address(text)),
+            Style::Symbol => write!(out, "{}", colors.symbol(text)),
         }
+    }
+}
+
+/// Collects pieces and the text they make up.
+struct TokenCollector {
+    text: String,
+    tokens: Vec<Token>,
+    memory: bool,
+}
+
+impl DisplaySink for TokenCollector {
+    fn token(&mut self, kind: TokenKind, text: &dyn fmt::Display) -> fmt::Result {
+        use fmt::Write;
 
+        let start = self.text.len();
+        write!(self.text, "{}", text)?;
+        self.tokens.push(Token {
+            kind,
+            span: start..self.text.len(),
+            memory: self.memory,
+        });
         Ok(())
     }
+
+    fn memory(&mut self, memory: bool) {
+        self.memory = memory;
+    }
+}
+
+/// The style of the mnemonic of an instruction with opcode `opcode`, or of a pseudo-instruction
+/// standing for one: what the instruction does.
+fn mnemonic_style(opcode: &Opcode) -> Style {
+    match opcode {
+        Opcode::Invalid | Opcode::Opaque => Style::Invalid,
+        Opcode::Custom(_) => Style::Misc,
+        Opcode::JAL
+        | Opcode::JALR
+        | Opcode::BEQ
+        | Opcode::BNE
+        | Opcode::BLT
+        | Opcode::BGE
+        | Opcode::BLTU
+        | Opcode::BGEU
+        | Opcode::CV_STARTI
+        | Opcode::CV_START
+        | Opcode::CV_ENDI
+        | Opcode::CV_END
+        | Opcode::CV_COUNTI
+        | Opcode::CV_COUNT
+        | Opcode::CV_SETUPI
+        | Opcode::CV_SETUP => Style::ControlFlow,
+        Opcode::LB
+        | Opcode::LH
+        | Opcode::LW
+        | Opcode::LBU
+        | Opcode::LHU
+        | Opcode::LWU
+        | Opcode::LD
+        | Opcode::SB
+        | Opcode::SH
+        | Opcode::SW
+        | Opcode::SD
+        | Opcode::TH_LBIA
+        | Opcode::TH_LBIB
+        | Opcode::TH_LHIA
+        | Opcode::TH_LHIB
+        | Opcode::TH_LWIA
+        | Opcode::TH_LWIB
+        | Opcode::TH_LDIA
+        | Opcode::TH_LDIB
+        | Opcode::TH_LBUIA
+        | Opcode::TH_LBUIB
+        | Opcode::TH_LHUIA
+        | Opcode::TH_LHUIB
+        | Opcode::TH_LWUIA
+        | Opcode::TH_LWUIB
+        | Opcode::TH_LRB
+        | Opcode::TH_LRH
+        | Opcode::TH_LRW
+        | Opcode::TH_LRD
+        | Opcode::TH_LRBU
+        | Opcode::TH_LRHU
+        | Opcode::TH_LRWU
+        | Opcode::TH_LURB
+        | Opcode::TH_LURH
+        | Opcode::TH_LURW
+        | Opcode::TH_LURD
+        | Opcode::TH_LURBU
+        | Opcode::TH_LURHU
+        | Opcode::TH_LURWU
+        | Opcode::TH_SBIA
+        | Opcode::TH_SBIB
+        | Opcode::TH_SHIA
+        | Opcode::TH_SHIB
+        | Opcode::TH_SWIA
+        | Opcode::TH_SWIB
+        | Opcode::TH_SDIA
+        | Opcode::TH_SDIB
+        | Opcode::TH_SRB
+        | Opcode::TH_SRH
+        | Opcode::TH_SRW
+        | Opcode::TH_SRD
+        | Opcode::TH_SURB
+        | Opcode::TH_SURH
+        | Opcode::TH_SURW
+        | Opcode::TH_SURD
+        | Opcode::TH_LWD
+        | Opcode::TH_LWUD
+        | Opcode::TH_LDD
+        | Opcode::TH_SWD
+        | Opcode::TH_SDD
+        | Opcode::TH_FLRW
+        | Opcode::TH_FLRD
+        | Opcode::TH_FLURW
+        | Opcode::TH_FLURD
+        | Opcode::TH_FSRW
+        | Opcode::TH_FSRD
+        | Opcode::TH_FSURW
+        | Opcode::TH_FSURD
+        | Opcode::CV_LB
+        | Opcode::CV_LBU
+        | Opcode::CV_LH
+        | Opcode::CV_LHU
+        | Opcode::CV_LW
+        | Opcode::CV_SB
+        | Opcode::CV_SH
+        | Opcode::CV_SW => Style::Data,
+        Opcode::SLT
+        | Opcode::SLTI
+        | Opcode::SLTU
+        | Opcode::SLTIU
+        | Opcode::TH_TST
+        | Opcode::TH_TSTNBZ
+        | Opcode::CV_SLET
+        | Opcode::CV_SLETU => Style::Comparison,
+        Opcode::PAUSE => Style::Nop,
+        Opcode::EBREAK => Style::Stop,
+        Opcode::ECALL
+        | Opcode::FENCE
+        | Opcode::FENCE_TSO
+        | Opcode::FENCE_I
+        | Opcode::CSRRW
+        | Opcode::CSRRS
+        | Opcode::CSRRC
+        | Opcode::CSRRWI
+        | Opcode::CSRRSI
+        | Opcode::CSRRCI
+        | Opcode::SINVAL_VMA
+        | Opcode::SFENCE_W_INVAL
+        | Opcode::SFENCE_INVAL_IR
+        | Opcode::HINVAL_VVMA
+        | Opcode::HINVAL_GVMA
+        | Opcode::TH_SYNC
+        | Opcode::TH_SYNC_S
+        | Opcode::TH_SYNC_I
+        | Opcode::TH_SYNC_IS
+        | Opcode::TH_SFENCE_VMAS
+        | Opcode::TH_DCACHE_CALL
+        | Opcode::TH_DCACHE_IALL
+        | Opcode::TH_DCACHE_CIALL
+        | Opcode::TH_DCACHE_CSW
+        | Opcode::TH_DCACHE_ISW
+        | Opcode::TH_DCACHE_CISW
+        | Opcode::TH_DCACHE_CVAL1
+        | Opcode::TH_DCACHE_CVA
+        | Opcode::TH_DCACHE_IVA
+        | Opcode::TH_DCACHE_CIVA
+        | Opcode::TH_DCACHE_CPAL1
+        | Opcode::TH_DCACHE_CPA
+        | Opcode::TH_DCACHE_IPA
+        | Opcode::TH_DCACHE_CIPA
+        | Opcode::TH_ICACHE_IALL
+        | Opcode::TH_ICACHE_IALLS
+        | Opcode::TH_ICACHE_IVA
+        | Opcode::TH_ICACHE_IPA
+        | Opcode::TH_L2CACHE_CALL
+        | Opcode::TH_L2CACHE_IALL
+        | Opcode::TH_L2CACHE_CIALL => Style::Platform,
+        _ => Style::Arithmetic,
+    }
 }
 
 impl fmt::Display for Operand {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Operand::Reg(reg) => {
-                let name = REG_NAMES[*reg as usize];
-                write!(f, "{}", name)
-            }
-            Operand::Imm(imm) => {
-                write!(f, "{:#x}", imm)
-            }
-            Operand::BaseOffset(reg, offs) => {
-                let name = REG_NAMES[*reg as usize];
-                if *offs == 0 {
-                    write!(f, "({})", name)
-                } else {
-                    if *offs < 0 {
-                        write!(f, "-{:#x}({})", offs.wrapping_neg(), name)
-                    } else {
-                        write!(f, "{:#x}({})", offs, name)
-                    }
-                }
-            }
-            Operand::Shift(sa) => {
-                write!(f, "{:#x}", sa)
-            }
-            Operand::LongImm(imm) => {
-                write!(f, "{:#x}", imm)
-            }
-            Operand::JOffset(offs) => {
-                if *offs < 0 {
-                    write!(f, "$-{:#x}", offs.wrapping_neg())
-                } else {
-                    write!(f, "$+{:#x}", offs)
-                }
+        render_operand(
+            &mut ColorSink {
+                palette: Colors(&NoColors),
+                out: f,
+            },
+            self,
+            &DisplayOptions::default(),
+        )
+    }
+}
+
+fn render_operand<S: DisplaySink + ?Sized>(
+    sink: &mut S,
+    op: &Operand,
+    options: &DisplayOptions,
+) -> fmt::Result {
+    let text = TokenKind::Text;
+    let separator = options.separator();
+    let reg = |sink: &mut S, reg: u8| {
+        sink.token(TokenKind::Register(Register::X(reg)), &options.gpr(reg))
+    };
+    let imm = |sink: &mut S, imm: Imm| sink.token(TokenKind::Immediate(imm.value), &imm);
+    match op {
+        Operand::Reg(r) => reg(sink, *r),
+        Operand::FReg(r) => sink.token(TokenKind::Register(Register::F(*r)), &options.fpr(*r)),
+        Operand::VReg(r) => sink.token(
+            TokenKind::Register(Register::V(*r)),
+            &format_args!("v{}", r),
+        ),
+        Operand::RoundingMode(rm) => sink.token(text, rm),
+        Operand::VMask => {
+            sink.token(TokenKind::Register(Register::V(0)), &"v0")?;
+            sink.token(text, &".t")
+        }
+        Operand::Imm(i) => imm(sink, options.signed_imm(*i, false)),
+        Operand::BaseOffset(base, offs) => {
+            sink.memory(true);
+            imm(sink, options.offset_imm(*offs as i32))?;
+            sink.token(text, &"(")?;
+            reg(sink, *base)?;
+            sink.token(text, &")")?;
+            sink.memory(false);
+            Ok(())
+        }
+        Operand::Base(base) => {
+            sink.memory(true);
+            sink.token(text, &"(")?;
+            reg(sink, *base)?;
+            sink.token(text, &")")?;
+            sink.memory(false);
+            Ok(())
+        }
+        Operand::PostIncrement(base, inc) => {
+            sink.memory(true);
+            sink.token(text, &"(")?;
+            reg(sink, *base)?;
+            sink.token(text, &")")?;
+            sink.token(text, &separator)?;
+            imm(sink, options.signed_imm(*inc as i32, false))?;
+            sink.memory(false);
+            Ok(())
+        }
+        Operand::PostIncrementReg(base, inc) => {
+            sink.memory(true);
+            sink.token(text, &"(")?;
+            reg(sink, *base)?;
+            sink.token(text, &")")?;
+            sink.token(text, &separator)?;
+            reg(sink, *inc)?;
+            sink.memory(false);
+            Ok(())
+        }
+        Operand::BaseIndex(base, index) => {
+            sink.memory(true);
+            reg(sink, *index)?;
+            sink.token(text, &"(")?;
+            reg(sink, *base)?;
+            sink.token(text, &")")?;
+            sink.memory(false);
+            Ok(())
+        }
+        Operand::Shift(sa) => imm(sink, options.imm(*sa as i64, true)),
+        Operand::LongImm(i) => imm(sink, options.imm(*i as i64, true)),
+        Operand::JOffset(offs) => {
+            // without an address, the target relative to the instruction
+            let sign = if *offs < 0 { '-' } else { '+' };
+            sink.token(
+                TokenKind::Immediate(*offs as i64),
+                &format_args!("${}{:#x}", sign, offs.unsigned_abs()),
+            )
+        }
+        Operand::Csr(csr) => match csr_name(*csr) {
+            Some(name) => sink.token(TokenKind::Csr(*csr), &name),
+            None => sink.token(TokenKind::Csr(*csr), &options.imm(*csr as i64, true)),
+        },
+        Operand::FenceSet(set) => sink.token(text, &FenceSet(*set)),
+    }
+}
+
+/// The accesses in a fence set, as letters.
+struct FenceSet(u8);
+
+impl fmt::Display for FenceSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "0");
+        }
+        for (bit, name) in [(8, "i"), (4, "o"), (2, "r"), (1, "w")] {
+            if self.0 & bit != 0 {
+                write!(f, "{}", name)?;
             }
         }
+        Ok(())
     }
 }
 
 impl fmt::Display for Opcode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Opcode::Invalid => panic!("attempt to disassemble invalid opcode"),
+            Opcode::Invalid => write!(f, ".insn"),
+            Opcode::Custom(mnemonic) => write!(f, "{}", mnemonic),
+            Opcode::Opaque => write!(f, ".insn"),
             Opcode::LUI => write!(f, "lui"),
             Opcode::AUIPC => write!(f, "auipc"),
             Opcode::JAL => write!(f, "jal"),
@@ -179,6 +1693,7 @@ impl fmt::Display for Opcode {
             Opcode::OR => write!(f, "or"),
             Opcode::AND => write!(f, "and"),
             Opcode::FENCE => write!(f, "fence"),
+            Opcode::FENCE_TSO => write!(f, "fence.tso"),
             Opcode::ECALL => write!(f, "ecall"),
             Opcode::EBREAK => write!(f, "ebreak"),
             Opcode::LWU => write!(f, "lwu"),
@@ -193,6 +1708,297 @@ impl fmt::Display for Opcode {
             Opcode::SLLW => write!(f, "sllw"),
             Opcode::SRLW => write!(f, "srlw"),
             Opcode::SRAW => write!(f, "sraw"),
+            Opcode::FENCE_I => write!(f, "fence.i"),
+            Opcode::CSRRW => write!(f, "csrrw"),
+            Opcode::CSRRS => write!(f, "csrrs"),
+            Opcode::CSRRC => write!(f, "csrrc"),
+            Opcode::CSRRWI => write!(f, "csrrwi"),
+            Opcode::CSRRSI => write!(f, "csrrsi"),
+            Opcode::CSRRCI => write!(f, "csrrci"),
+            Opcode::FSGNJ_S => write!(f, "fsgnj.s"),
+            Opcode::FSGNJN_S => write!(f, "fsgnjn.s"),
+            Opcode::FSGNJX_S => write!(f, "fsgnjx.s"),
+            Opcode::FSGNJ_D => write!(f, "fsgnj.d"),
+            Opcode::FSGNJN_D => write!(f, "fsgnjn.d"),
+            Opcode::FSGNJX_D => write!(f, "fsgnjx.d"),
+            Opcode::PAUSE => write!(f, "pause"),
+            Opcode::FCVT_BF16_S => write!(f, "fcvt.bf16.s"),
+            Opcode::FCVT_S_BF16 => write!(f, "fcvt.s.bf16"),
+            Opcode::VFNCVTBF16_F_F_W => write!(f, "vfncvtbf16.f.f.w"),
+            Opcode::VFWCVTBF16_F_F_V => write!(f, "vfwcvtbf16.f.f.v"),
+            Opcode::VFWMACCBF16_VV => write!(f, "vfwmaccbf16.vv"),
+            Opcode::VFWMACCBF16_VF => write!(f, "vfwmaccbf16.vf"),
+            Opcode::SINVAL_VMA => write!(f, "sinval.vma"),
+            Opcode::SFENCE_W_INVAL => write!(f, "sfence.w.inval"),
+            Opcode::SFENCE_INVAL_IR => write!(f, "sfence.inval.ir"),
+            Opcode::HINVAL_VVMA => write!(f, "hinval.vvma"),
+            Opcode::HINVAL_GVMA => write!(f, "hinval.gvma"),
+            Opcode::ADD16 => write!(f, "add16"),
+            Opcode::RADD16 => write!(f, "radd16"),
+            Opcode::URADD16 => write!(f, "uradd16"),
+            Opcode::KADD16 => write!(f, "kadd16"),
+            Opcode::UKADD16 => write!(f, "ukadd16"),
+            Opcode::SUB16 => write!(f, "sub16"),
+            Opcode::RSUB16 => write!(f, "rsub16"),
+            Opcode::URSUB16 => write!(f, "ursub16"),
+            Opcode::KSUB16 => write!(f, "ksub16"),
+            Opcode::UKSUB16 => write!(f, "uksub16"),
+            Opcode::CRAS16 => write!(f, "cras16"),
+            Opcode::RCRAS16 => write!(f, "rcras16"),
+            Opcode::KCRAS16 => write!(f, "kcras16"),
+            Opcode::CRSA16 => write!(f, "crsa16"),
+            Opcode::RCRSA16 => write!(f, "rcrsa16"),
+            Opcode::KCRSA16 => write!(f, "kcrsa16"),
+            Opcode::ADD8 => write!(f, "add8"),
+            Opcode::RADD8 => write!(f, "radd8"),
+            Opcode::URADD8 => write!(f, "uradd8"),
+            Opcode::KADD8 => write!(f, "kadd8"),
+            Opcode::UKADD8 => write!(f, "ukadd8"),
+            Opcode::SUB8 => write!(f, "sub8"),
+            Opcode::RSUB8 => write!(f, "rsub8"),
+            Opcode::URSUB8 => write!(f, "ursub8"),
+            Opcode::KSUB8 => write!(f, "ksub8"),
+            Opcode::UKSUB8 => write!(f, "uksub8"),
+            Opcode::SRA16 => write!(f, "sra16"),
+            Opcode::SRL16 => write!(f, "srl16"),
+            Opcode::SLL16 => write!(f, "sll16"),
+            Opcode::SRA8 => write!(f, "sra8"),
+            Opcode::SRL8 => write!(f, "srl8"),
+            Opcode::SLL8 => write!(f, "sll8"),
+            Opcode::CMPEQ16 => write!(f, "cmpeq16"),
+            Opcode::SCMPLT16 => write!(f, "scmplt16"),
+            Opcode::SCMPLE16 => write!(f, "scmple16"),
+            Opcode::UCMPLT16 => write!(f, "ucmplt16"),
+            Opcode::UCMPLE16 => write!(f, "ucmple16"),
+            Opcode::CMPEQ8 => write!(f, "cmpeq8"),
+            Opcode::SCMPLT8 => write!(f, "scmplt8"),
+            Opcode::SCMPLE8 => write!(f, "scmple8"),
+            Opcode::UCMPLT8 => write!(f, "ucmplt8"),
+            Opcode::UCMPLE8 => write!(f, "ucmple8"),
+            Opcode::SMIN16 => write!(f, "smin16"),
+            Opcode::UMIN16 => write!(f, "umin16"),
+            Opcode::SMAX16 => write!(f, "smax16"),
+            Opcode::UMAX16 => write!(f, "umax16"),
+            Opcode::SMIN8 => write!(f, "smin8"),
+            Opcode::UMIN8 => write!(f, "umin8"),
+            Opcode::SMAX8 => write!(f, "smax8"),
+            Opcode::UMAX8 => write!(f, "umax8"),
+            Opcode::KHM16 => write!(f, "khm16"),
+            Opcode::KHMX16 => write!(f, "khmx16"),
+            Opcode::KHM8 => write!(f, "khm8"),
+            Opcode::KHMX8 => write!(f, "khmx8"),
+            Opcode::SMAQA => write!(f, "smaqa"),
+            Opcode::UMAQA => write!(f, "umaqa"),
+            Opcode::SMAQA_SU => write!(f, "smaqa.su"),
+            Opcode::PKBB16 => write!(f, "pkbb16"),
+            Opcode::PKBT16 => write!(f, "pkbt16"),
+            Opcode::PKTB16 => write!(f, "pktb16"),
+            Opcode::PKTT16 => write!(f, "pktt16"),
+            Opcode::TH_ADDSL => write!(f, "th.addsl"),
+            Opcode::TH_SRRI => write!(f, "th.srri"),
+            Opcode::TH_SRRIW => write!(f, "th.srriw"),
+            Opcode::TH_EXT => write!(f, "th.ext"),
+            Opcode::TH_EXTU => write!(f, "th.extu"),
+            Opcode::TH_FF0 => write!(f, "th.ff0"),
+            Opcode::TH_FF1 => write!(f, "th.ff1"),
+            Opcode::TH_REV => write!(f, "th.rev"),
+            Opcode::TH_REVW => write!(f, "th.revw"),
+            Opcode::TH_TSTNBZ => write!(f, "th.tstnbz"),
+            Opcode::TH_TST => write!(f, "th.tst"),
+            Opcode::TH_MVEQZ => write!(f, "th.mveqz"),
+            Opcode::TH_MVNEZ => write!(f, "th.mvnez"),
+            Opcode::TH_MULA => write!(f, "th.mula"),
+            Opcode::TH_MULAH => write!(f, "th.mulah"),
+            Opcode::TH_MULAW => write!(f, "th.mulaw"),
+            Opcode::TH_MULS => write!(f, "th.muls"),
+            Opcode::TH_MULSH => write!(f, "th.mulsh"),
+            Opcode::TH_MULSW => write!(f, "th.mulsw"),
+            Opcode::TH_LBIA => write!(f, "th.lbia"),
+            Opcode::TH_LBIB => write!(f, "th.lbib"),
+            Opcode::TH_LHIA => write!(f, "th.lhia"),
+            Opcode::TH_LHIB => write!(f, "th.lhib"),
+            Opcode::TH_LWIA => write!(f, "th.lwia"),
+            Opcode::TH_LWIB => write!(f, "th.lwib"),
+            Opcode::TH_LDIA => write!(f, "th.ldia"),
+            Opcode::TH_LDIB => write!(f, "th.ldib"),
+            Opcode::TH_LBUIA => write!(f, "th.lbuia"),
+            Opcode::TH_LBUIB => write!(f, "th.lbuib"),
+            Opcode::TH_LHUIA => write!(f, "th.lhuia"),
+            Opcode::TH_LHUIB => write!(f, "th.lhuib"),
+            Opcode::TH_LWUIA => write!(f, "th.lwuia"),
+            Opcode::TH_LWUIB => write!(f, "th.lwuib"),
+            Opcode::TH_LRB => write!(f, "th.lrb"),
+            Opcode::TH_LRH => write!(f, "th.lrh"),
+            Opcode::TH_LRW => write!(f, "th.lrw"),
+            Opcode::TH_LRD => write!(f, "th.lrd"),
+            Opcode::TH_LRBU => write!(f, "th.lrbu"),
+            Opcode::TH_LRHU => write!(f, "th.lrhu"),
+            Opcode::TH_LRWU => write!(f, "th.lrwu"),
+            Opcode::TH_LURB => write!(f, "th.lurb"),
+            Opcode::TH_LURH => write!(f, "th.lurh"),
+            Opcode::TH_LURW => write!(f, "th.lurw"),
+            Opcode::TH_LURD => write!(f, "th.lurd"),
+            Opcode::TH_LURBU => write!(f, "th.lurbu"),
+            Opcode::TH_LURHU => write!(f, "th.lurhu"),
+            Opcode::TH_LURWU => write!(f, "th.lurwu"),
+            Opcode::TH_SBIA => write!(f, "th.sbia"),
+            Opcode::TH_SBIB => write!(f, "th.sbib"),
+            Opcode::TH_SHIA => write!(f, "th.shia"),
+            Opcode::TH_SHIB => write!(f, "th.shib"),
+            Opcode::TH_SWIA => write!(f, "th.swia"),
+            Opcode::TH_SWIB => write!(f, "th.swib"),
+            Opcode::TH_SDIA => write!(f, "th.sdia"),
+            Opcode::TH_SDIB => write!(f, "th.sdib"),
+            Opcode::TH_SRB => write!(f, "th.srb"),
+            Opcode::TH_SRH => write!(f, "th.srh"),
+            Opcode::TH_SRW => write!(f, "th.srw"),
+            Opcode::TH_SRD => write!(f, "th.srd"),
+            Opcode::TH_SURB => write!(f, "th.surb"),
+            Opcode::TH_SURH => write!(f, "th.surh"),
+            Opcode::TH_SURW => write!(f, "th.surw"),
+            Opcode::TH_SURD => write!(f, "th.surd"),
+            Opcode::TH_LWD => write!(f, "th.lwd"),
+            Opcode::TH_LWUD => write!(f, "th.lwud"),
+            Opcode::TH_LDD => write!(f, "th.ldd"),
+            Opcode::TH_SWD => write!(f, "th.swd"),
+            Opcode::TH_SDD => write!(f, "th.sdd"),
+            Opcode::TH_FLRW => write!(f, "th.flrw"),
+            Opcode::TH_FLRD => write!(f, "th.flrd"),
+            Opcode::TH_FLURW => write!(f, "th.flurw"),
+            Opcode::TH_FLURD => write!(f, "th.flurd"),
+            Opcode::TH_FSRW => write!(f, "th.fsrw"),
+            Opcode::TH_FSRD => write!(f, "th.fsrd"),
+            Opcode::TH_FSURW => write!(f, "th.fsurw"),
+            Opcode::TH_FSURD => write!(f, "th.fsurd"),
+            Opcode::TH_SYNC => write!(f, "th.sync"),
+            Opcode::TH_SYNC_S => write!(f, "th.sync.s"),
+            Opcode::TH_SYNC_I => write!(f, "th.sync.i"),
+            Opcode::TH_SYNC_IS => write!(f, "th.sync.is"),
+            Opcode::TH_SFENCE_VMAS => write!(f, "th.sfence.vmas"),
+            Opcode::TH_DCACHE_CALL => write!(f, "th.dcache.call"),
+            Opcode::TH_DCACHE_IALL => write!(f, "th.dcache.iall"),
+            Opcode::TH_DCACHE_CIALL => write!(f, "th.dcache.ciall"),
+            Opcode::TH_DCACHE_CSW => write!(f, "th.dcache.csw"),
+            Opcode::TH_DCACHE_ISW => write!(f, "th.dcache.isw"),
+            Opcode::TH_DCACHE_CISW => write!(f, "th.dcache.cisw"),
+            Opcode::TH_DCACHE_CVAL1 => write!(f, "th.dcache.cval1"),
+            Opcode::TH_DCACHE_CVA => write!(f, "th.dcache.cva"),
+            Opcode::TH_DCACHE_IVA => write!(f, "th.dcache.iva"),
+            Opcode::TH_DCACHE_CIVA => write!(f, "th.dcache.civa"),
+            Opcode::TH_DCACHE_CPAL1 => write!(f, "th.dcache.cpal1"),
+            Opcode::TH_DCACHE_CPA => write!(f, "th.dcache.cpa"),
+            Opcode::TH_DCACHE_IPA => write!(f, "th.dcache.ipa"),
+            Opcode::TH_DCACHE_CIPA => write!(f, "th.dcache.cipa"),
+            Opcode::TH_ICACHE_IALL => write!(f, "th.icache.iall"),
+            Opcode::TH_ICACHE_IALLS => write!(f, "th.icache.ialls"),
+            Opcode::TH_ICACHE_IVA => write!(f, "th.icache.iva"),
+            Opcode::TH_ICACHE_IPA => write!(f, "th.icache.ipa"),
+            Opcode::TH_L2CACHE_CALL => write!(f, "th.l2cache.call"),
+            Opcode::TH_L2CACHE_IALL => write!(f, "th.l2cache.iall"),
+            Opcode::TH_L2CACHE_CIALL => write!(f, "th.l2cache.ciall"),
+            Opcode::CV_STARTI => write!(f, "cv.starti"),
+            Opcode::CV_START => write!(f, "cv.start"),
+            Opcode::CV_ENDI => write!(f, "cv.endi"),
+            Opcode::CV_END => write!(f, "cv.end"),
+            Opcode::CV_COUNTI => write!(f, "cv.counti"),
+            Opcode::CV_COUNT => write!(f, "cv.count"),
+            Opcode::CV_SETUPI => write!(f, "cv.setupi"),
+            Opcode::CV_SETUP => write!(f, "cv.setup"),
+            Opcode::CV_LB => write!(f, "cv.lb"),
+            Opcode::CV_LBU => write!(f, "cv.lbu"),
+            Opcode::CV_LH => write!(f, "cv.lh"),
+            Opcode::CV_LHU => write!(f, "cv.lhu"),
+            Opcode::CV_LW => write!(f, "cv.lw"),
+            Opcode::CV_SB => write!(f, "cv.sb"),
+            Opcode::CV_SH => write!(f, "cv.sh"),
+            Opcode::CV_SW => write!(f, "cv.sw"),
+            Opcode::CV_MAC => write!(f, "cv.mac"),
+            Opcode::CV_MSU => write!(f, "cv.msu"),
+            Opcode::CV_ABS => write!(f, "cv.abs"),
+            Opcode::CV_SLET => write!(f, "cv.slet"),
+            Opcode::CV_SLETU => write!(f, "cv.sletu"),
+            Opcode::CV_MIN => write!(f, "cv.min"),
+            Opcode::CV_MINU => write!(f, "cv.minu"),
+            Opcode::CV_MAX => write!(f, "cv.max"),
+            Opcode::CV_MAXU => write!(f, "cv.maxu"),
+            Opcode::CV_EXTHS => write!(f, "cv.exths"),
+            Opcode::CV_EXTHZ => write!(f, "cv.exthz"),
+            Opcode::CV_EXTBS => write!(f, "cv.extbs"),
+            Opcode::CV_EXTBZ => write!(f, "cv.extbz"),
+            Opcode::CV_CLIP => write!(f, "cv.clip"),
+            Opcode::CV_CLIPU => write!(f, "cv.clipu"),
+            Opcode::CV_CLIPR => write!(f, "cv.clipr"),
+            Opcode::CV_CLIPUR => write!(f, "cv.clipur"),
+            Opcode::CV_ADD_H => write!(f, "cv.add.h"),
+            Opcode::CV_ADD_B => write!(f, "cv.add.b"),
+            Opcode::CV_ADD_SC_H => write!(f, "cv.add.sc.h"),
+            Opcode::CV_ADD_SC_B => write!(f, "cv.add.sc.b"),
+            Opcode::CV_SUB_H => write!(f, "cv.sub.h"),
+            Opcode::CV_SUB_B => write!(f, "cv.sub.b"),
+            Opcode::CV_SUB_SC_H => write!(f, "cv.sub.sc.h"),
+            Opcode::CV_SUB_SC_B => write!(f, "cv.sub.sc.b"),
+            Opcode::CV_AVG_H => write!(f, "cv.avg.h"),
+            Opcode::CV_AVG_B => write!(f, "cv.avg.b"),
+            Opcode::CV_AVG_SC_H => write!(f, "cv.avg.sc.h"),
+            Opcode::CV_AVG_SC_B => write!(f, "cv.avg.sc.b"),
+            Opcode::CV_AVGU_H => write!(f, "cv.avgu.h"),
+            Opcode::CV_AVGU_B => write!(f, "cv.avgu.b"),
+            Opcode::CV_AVGU_SC_H => write!(f, "cv.avgu.sc.h"),
+            Opcode::CV_AVGU_SC_B => write!(f, "cv.avgu.sc.b"),
+            Opcode::CV_MIN_H => write!(f, "cv.min.h"),
+            Opcode::CV_MIN_B => write!(f, "cv.min.b"),
+            Opcode::CV_MIN_SC_H => write!(f, "cv.min.sc.h"),
+            Opcode::CV_MIN_SC_B => write!(f, "cv.min.sc.b"),
+            Opcode::CV_MINU_H => write!(f, "cv.minu.h"),
+            Opcode::CV_MINU_B => write!(f, "cv.minu.b"),
+            Opcode::CV_MINU_SC_H => write!(f, "cv.minu.sc.h"),
+            Opcode::CV_MINU_SC_B => write!(f, "cv.minu.sc.b"),
+            Opcode::CV_MAX_H => write!(f, "cv.max.h"),
+            Opcode::CV_MAX_B => write!(f, "cv.max.b"),
+            Opcode::CV_MAX_SC_H => write!(f, "cv.max.sc.h"),
+            Opcode::CV_MAX_SC_B => write!(f, "cv.max.sc.b"),
+            Opcode::CV_MAXU_H => write!(f, "cv.maxu.h"),
+            Opcode::CV_MAXU_B => write!(f, "cv.maxu.b"),
+            Opcode::CV_MAXU_SC_H => write!(f, "cv.maxu.sc.h"),
+            Opcode::CV_MAXU_SC_B => write!(f, "cv.maxu.sc.b"),
+            Opcode::CV_SRL_H => write!(f, "cv.srl.h"),
+            Opcode::CV_SRL_B => write!(f, "cv.srl.b"),
+            Opcode::CV_SRL_SC_H => write!(f, "cv.srl.sc.h"),
+            Opcode::CV_SRL_SC_B => write!(f, "cv.srl.sc.b"),
+            Opcode::CV_SRA_H => write!(f, "cv.sra.h"),
+            Opcode::CV_SRA_B => write!(f, "cv.sra.b"),
+            Opcode::CV_SRA_SC_H => write!(f, "cv.sra.sc.h"),
+            Opcode::CV_SRA_SC_B => write!(f, "cv.sra.sc.b"),
+            Opcode::CV_SLL_H => write!(f, "cv.sll.h"),
+            Opcode::CV_SLL_B => write!(f, "cv.sll.b"),
+            Opcode::CV_SLL_SC_H => write!(f, "cv.sll.sc.h"),
+            Opcode::CV_SLL_SC_B => write!(f, "cv.sll.sc.b"),
+            Opcode::CV_OR_H => write!(f, "cv.or.h"),
+            Opcode::CV_OR_B => write!(f, "cv.or.b"),
+            Opcode::CV_OR_SC_H => write!(f, "cv.or.sc.h"),
+            Opcode::CV_OR_SC_B => write!(f, "cv.or.sc.b"),
+            Opcode::CV_XOR_H => write!(f, "cv.xor.h"),
+            Opcode::CV_XOR_B => write!(f, "cv.xor.b"),
+            Opcode::CV_XOR_SC_H => write!(f, "cv.xor.sc.h"),
+            Opcode::CV_XOR_SC_B => write!(f, "cv.xor.sc.b"),
+            Opcode::CV_AND_H => write!(f, "cv.and.h"),
+            Opcode::CV_AND_B => write!(f, "cv.and.b"),
+            Opcode::CV_AND_SC_H => write!(f, "cv.and.sc.h"),
+            Opcode::CV_AND_SC_B => write!(f, "cv.and.sc.b"),
+            Opcode::CV_ABS_H => write!(f, "cv.abs.h"),
+            Opcode::CV_ABS_B => write!(f, "cv.abs.b"),
+        }
+    }
+}
+
+impl fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoundingMode::RNE => write!(f, "rne"),
+            RoundingMode::RTZ => write!(f, "rtz"),
+            RoundingMode::RDN => write!(f, "rdn"),
+            RoundingMode::RUP => write!(f, "rup"),
+            RoundingMode::RMM => write!(f, "rmm"),
         }
     }
 }
@@ -0,0 +1,1066 @@
+//! ISA extensions, and configuring a [`RiscVDecoder`] from an ISA string or profile name.
+
+use std::fmt;
+
+use crate::{Instruction, Opcode, RiscVDecoder};
+
+/// A RISC-V ISA extension, named as in an ISA string.
+///
+/// Instructions from an extension are only decoded once it is enabled on the [`RiscVDecoder`].
+/// Every standard extension is enabled by default; vendor extensions must be enabled explicitly.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Extension {
+    /// Base integer instruction set
+    I,
+    /// Integer multiplication and division
+    M,
+    /// Atomic instructions
+    A,
+    /// Single-precision floating point
+    F,
+    /// Double-precision floating point
+    D,
+    /// Quad-precision floating point
+    Q,
+    /// Compressed instructions
+    C,
+    /// Vector operations
+    V,
+    /// Hypervisor
+    H,
+    /// Packed SIMD (draft)
+    P,
+    /// Control and status register instructions
+    Zicsr,
+    /// Instruction-fetch fence
+    Zifencei,
+    /// Base counters and timers
+    Zicntr,
+    /// Hardware performance counters
+    Zihpm,
+    /// Pause hint
+    Zihintpause,
+    /// Non-temporal locality hints
+    Zihintntl,
+    /// Integer conditional operations
+    Zicond,
+    /// May-be-operations
+    Zimop,
+    /// Cache-block management
+    Zicbom,
+    /// Cache-block prefetch
+    Zicbop,
+    /// Cache-block zero
+    Zicboz,
+    /// Main memory supports instruction fetch with atomicity
+    Ziccif,
+    /// Main memory supports forward progress on LR/SC sequences
+    Ziccrse,
+    /// Main memory supports all atomics in A
+    Ziccamoa,
+    /// Main memory supports misaligned loads and stores
+    Zicclsm,
+    /// 64-byte cache blocks
+    Zic64b,
+    /// 64-byte reservation sets
+    Za64rs,
+    /// 128-byte reservation sets
+    Za128rs,
+    /// Atomic memory operations
+    Zaamo,
+    /// Load-reserved and store-conditional
+    Zalrsc,
+    /// Wait-on-reservation-set
+    Zawrs,
+    /// Integer multiplication
+    Zmmul,
+    /// Address generation
+    Zba,
+    /// Basic bit manipulation
+    Zbb,
+    /// Carry-less multiplication
+    Zbc,
+    /// Single-bit instructions
+    Zbs,
+    /// Half-precision floating point
+    Zfh,
+    /// Minimal half-precision floating point
+    Zfhmin,
+    /// Additional floating-point instructions
+    Zfa,
+    /// Scalar bfloat16 conversions
+    Zfbfmin,
+    /// Compressed instructions other than floating-point loads and stores
+    Zca,
+    /// Additional compressed instructions
+    Zcb,
+    /// Compressed single-precision floating-point loads and stores
+    Zcf,
+    /// Compressed double-precision floating-point loads and stores
+    Zcd,
+    /// Compressed may-be-operations
+    Zcmop,
+    /// Data-independent execution latency
+    Zkt,
+    /// Vector basic bit manipulation
+    Zvbb,
+    /// Vector operations for embedded processors, with 32-bit elements
+    Zve32x,
+    /// Vector operations for embedded processors, with single-precision floating point
+    Zve32f,
+    /// Vector operations for embedded processors, with 64-bit elements
+    Zve64x,
+    /// Vector operations for embedded processors, with 64-bit elements and single-precision floating point
+    Zve64f,
+    /// Vector operations for embedded processors, with double-precision floating point
+    Zve64d,
+    /// Vector data-independent execution latency
+    Zvkt,
+    /// Vector minimal half-precision floating point
+    Zvfhmin,
+    /// Vector bfloat16 conversions
+    Zvfbfmin,
+    /// Vector bfloat16 widening multiply-add
+    Zvfbfwma,
+    /// Fine-grained address-translation cache invalidation
+    Svinval,
+    /// Pointer masking
+    Supm,
+    /// T-Head address calculation
+    XTheadBa,
+    /// T-Head basic bit manipulation
+    XTheadBb,
+    /// T-Head single-bit instructions
+    XTheadBs,
+    /// T-Head conditional move
+    XTheadCondMov,
+    /// T-Head indexed and base-updating memory operations
+    XTheadMemIdx,
+    /// T-Head two-register memory operations
+    XTheadMemPair,
+    /// T-Head multiply-accumulate
+    XTheadMac,
+    /// T-Head indexed floating-point memory operations
+    XTheadFMemIdx,
+    /// T-Head multi-core synchronization
+    XTheadSync,
+    /// T-Head cache management operations
+    XTheadCmo,
+    /// CORE-V hardware loops
+    XCVhwlp,
+    /// CORE-V post-increment and register-indexed memory operations
+    XCVmem,
+    /// CORE-V multiply-accumulate
+    XCVmac,
+    /// CORE-V miscellaneous ALU operations
+    XCValu,
+    /// CORE-V packed SIMD
+    XCVsimd,
+}
+
+impl Extension {
+    /// Every extension, in canonical ISA string order.
+    pub const ALL: [Extension; 73] = [
+        Extension::I,
+        Extension::M,
+        Extension::A,
+        Extension::F,
+        Extension::D,
+        Extension::Q,
+        Extension::C,
+        Extension::V,
+        Extension::H,
+        Extension::P,
+        Extension::Zicsr,
+        Extension::Zifencei,
+        Extension::Zicntr,
+        Extension::Zihpm,
+        Extension::Zihintpause,
+        Extension::Zihintntl,
+        Extension::Zicond,
+        Extension::Zimop,
+        Extension::Zicbom,
+        Extension::Zicbop,
+        Extension::Zicboz,
+        Extension::Ziccif,
+        Extension::Ziccrse,
+        Extension::Ziccamoa,
+        Extension::Zicclsm,
+        Extension::Zic64b,
+        Extension::Za64rs,
+        Extension::Za128rs,
+        Extension::Zaamo,
+        Extension::Zalrsc,
+        Extension::Zawrs,
+        Extension::Zmmul,
+        Extension::Zba,
+        Extension::Zbb,
+        Extension::Zbc,
+        Extension::Zbs,
+        Extension::Zfh,
+        Extension::Zfhmin,
+        Extension::Zfa,
+        Extension::Zfbfmin,
+        Extension::Zca,
+        Extension::Zcb,
+        Extension::Zcf,
+        Extension::Zcd,
+        Extension::Zcmop,
+        Extension::Zkt,
+        Extension::Zvbb,
+        Extension::Zve32x,
+        Extension::Zve32f,
+        Extension::Zve64x,
+        Extension::Zve64f,
+        Extension::Zve64d,
+        Extension::Zvkt,
+        Extension::Zvfhmin,
+        Extension::Zvfbfmin,
+        Extension::Zvfbfwma,
+        Extension::Svinval,
+        Extension::Supm,
+        Extension::XTheadBa,
+        Extension::XTheadBb,
+        Extension::XTheadBs,
+        Extension::XTheadCondMov,
+        Extension::XTheadMemIdx,
+        Extension::XTheadMemPair,
+        Extension::XTheadMac,
+        Extension::XTheadFMemIdx,
+        Extension::XTheadSync,
+        Extension::XTheadCmo,
+        Extension::XCVhwlp,
+        Extension::XCVmem,
+        Extension::XCVmac,
+        Extension::XCValu,
+        Extension::XCVsimd,
+    ];
+
+    /// Every T-Head vendor extension, as implemented by the C906 and C910.
+    pub const THEAD: [Extension; 10] = [
+        Extension::XTheadBa,
+        Extension::XTheadBb,
+        Extension::XTheadBs,
+        Extension::XTheadCondMov,
+        Extension::XTheadMemIdx,
+        Extension::XTheadMemPair,
+        Extension::XTheadMac,
+        Extension::XTheadFMemIdx,
+        Extension::XTheadSync,
+        Extension::XTheadCmo,
+    ];
+
+    /// Every CORE-V vendor extension, as implemented by the CV32E40P.
+    pub const COREV: [Extension; 5] = [
+        Extension::XCVhwlp,
+        Extension::XCVmem,
+        Extension::XCVmac,
+        Extension::XCValu,
+        Extension::XCVsimd,
+    ];
+
+    /// The extension's name as it appears in an ISA string.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Extension::I => "i",
+            Extension::M => "m",
+            Extension::A => "a",
+            Extension::F => "f",
+            Extension::D => "d",
+            Extension::Q => "q",
+            Extension::C => "c",
+            Extension::V => "v",
+            Extension::H => "h",
+            Extension::P => "p",
+            Extension::Zicsr => "zicsr",
+            Extension::Zifencei => "zifencei",
+            Extension::Zicntr => "zicntr",
+            Extension::Zihpm => "zihpm",
+            Extension::Zihintpause => "zihintpause",
+            Extension::Zihintntl => "zihintntl",
+            Extension::Zicond => "zicond",
+            Extension::Zimop => "zimop",
+            Extension::Zicbom => "zicbom",
+            Extension::Zicbop => "zicbop",
+            Extension::Zicboz => "zicboz",
+            Extension::Ziccif => "ziccif",
+            Extension::Ziccrse => "ziccrse",
+            Extension::Ziccamoa => "ziccamoa",
+            Extension::Zicclsm => "zicclsm",
+            Extension::Zic64b => "zic64b",
+            Extension::Za64rs => "za64rs",
+            Extension::Za128rs => "za128rs",
+            Extension::Zaamo => "zaamo",
+            Extension::Zalrsc => "zalrsc",
+            Extension::Zawrs => "zawrs",
+            Extension::Zmmul => "zmmul",
+            Extension::Zba => "zba",
+            Extension::Zbb => "zbb",
+            Extension::Zbc => "zbc",
+            Extension::Zbs => "zbs",
+            Extension::Zfh => "zfh",
+            Extension::Zfhmin => "zfhmin",
+            Extension::Zfa => "zfa",
+            Extension::Zfbfmin => "zfbfmin",
+            Extension::Zca => "zca",
+            Extension::Zcb => "zcb",
+            Extension::Zcf => "zcf",
+            Extension::Zcd => "zcd",
+            Extension::Zcmop => "zcmop",
+            Extension::Zkt => "zkt",
+            Extension::Zvbb => "zvbb",
+            Extension::Zve32x => "zve32x",
+            Extension::Zve32f => "zve32f",
+            Extension::Zve64x => "zve64x",
+            Extension::Zve64f => "zve64f",
+            Extension::Zve64d => "zve64d",
+            Extension::Zvkt => "zvkt",
+            Extension::Zvfhmin => "zvfhmin",
+            Extension::Zvfbfmin => "zvfbfmin",
+            Extension::Zvfbfwma => "zvfbfwma",
+            Extension::Svinval => "svinval",
+            Extension::Supm => "supm",
+            Extension::XTheadBa => "xtheadba",
+            Extension::XTheadBb => "xtheadbb",
+            Extension::XTheadBs => "xtheadbs",
+            Extension::XTheadCondMov => "xtheadcondmov",
+            Extension::XTheadMemIdx => "xtheadmemidx",
+            Extension::XTheadMemPair => "xtheadmempair",
+            Extension::XTheadMac => "xtheadmac",
+            Extension::XTheadFMemIdx => "xtheadfmemidx",
+            Extension::XTheadSync => "xtheadsync",
+            Extension::XTheadCmo => "xtheadcmo",
+            Extension::XCVhwlp => "xcvhwlp",
+            Extension::XCVmem => "xcvmem",
+            Extension::XCVmac => "xcvmac",
+            Extension::XCValu => "xcvalu",
+            Extension::XCVsimd => "xcvsimd",
+        }
+    }
+
+    /// Look up an extension by its ISA string name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Extension> {
+        Extension::ALL
+            .iter()
+            .find(|ext| ext.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Is this a vendor (`X`-prefixed) extension?
+    pub fn is_vendor(&self) -> bool {
+        self.name().starts_with('x')
+    }
+
+    /// Extensions that this extension depends on, and which an ISA string implies along with it.
+    fn implies(&self) -> &'static [Extension] {
+        match self {
+            Extension::M => &[Extension::Zmmul],
+            Extension::A => &[Extension::Zaamo, Extension::Zalrsc],
+            Extension::F => &[Extension::Zicsr],
+            Extension::D => &[Extension::F],
+            Extension::Q => &[Extension::D],
+            Extension::C => &[Extension::Zca],
+            Extension::V => &[Extension::Zve64d],
+            Extension::Zve64d => &[Extension::Zve64f, Extension::D],
+            Extension::Zve64f => &[Extension::Zve64x, Extension::Zve32f],
+            Extension::Zve64x => &[Extension::Zve32x],
+            Extension::Zve32f => &[Extension::Zve32x, Extension::F],
+            Extension::Zve32x => &[Extension::Zicsr],
+            Extension::Zicntr | Extension::Zihpm => &[Extension::Zicsr],
+            Extension::Zfh => &[Extension::Zfhmin],
+            Extension::Zfhmin | Extension::Zfa | Extension::Zfbfmin => &[Extension::F],
+            Extension::Zcb => &[Extension::Zca],
+            Extension::Zcf => &[Extension::Zca, Extension::F],
+            Extension::Zcd => &[Extension::Zca, Extension::D],
+            Extension::Zvfbfmin => &[Extension::Zve32f],
+            Extension::Zvfbfwma => &[Extension::Zvfbfmin, Extension::Zfbfmin],
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for Extension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A set of [`Extension`]s.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Extensions(u128);
+
+impl Extensions {
+    /// The empty set.
+    pub fn new() -> Self {
+        Extensions(0)
+    }
+
+    /// Every standard (non-vendor) extension.
+    pub fn standard() -> Self {
+        Extension::ALL
+            .iter()
+            .filter(|ext| !ext.is_vendor())
+            .copied()
+            .collect()
+    }
+
+    pub fn insert(&mut self, extension: Extension) {
+        self.0 |= 1 << extension as u32;
+    }
+
+    pub fn remove(&mut self, extension: Extension) {
+        self.0 &= !(1 << extension as u32);
+    }
+
+    pub fn contains(&self, extension: Extension) -> bool {
+        self.0 & (1 << extension as u32) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The extensions in either `self` or `other`.
+    pub fn union(&self, other: Extensions) -> Extensions {
+        Extensions(self.0 | other.0)
+    }
+
+    /// The extensions required by every instruction in `instructions`.
+    pub fn used_by<'a, I: IntoIterator<Item = &'a Instruction>>(instructions: I) -> Extensions {
+        instructions
+            .into_iter()
+            .fold(Extensions::new(), |used, instr| {
+                used.union(instr.extensions())
+            })
+    }
+
+    /// The extensions in this set, in canonical ISA string order.
+    pub fn iter(&self) -> impl Iterator<Item = Extension> + '_ {
+        Extension::ALL
+            .iter()
+            .copied()
+            .filter(move |ext| self.contains(*ext))
+    }
+
+    /// Add every extension implied by those already in the set.
+    fn close_over_implications(&mut self) {
+        loop {
+            let before = *self;
+            for ext in before.iter() {
+                for implied in ext.implies() {
+                    self.insert(*implied);
+                }
+            }
+            if *self == before {
+                return;
+            }
+        }
+    }
+}
+
+impl Extend<Extension> for Extensions {
+    fn extend<T: IntoIterator<Item = Extension>>(&mut self, iter: T) {
+        for ext in iter {
+            self.insert(ext);
+        }
+    }
+}
+
+impl std::iter::FromIterator<Extension> for Extensions {
+    fn from_iter<T: IntoIterator<Item = Extension>>(iter: T) -> Self {
+        let mut set = Extensions::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Width of the integer registers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Xlen {
+    X32,
+    /// The default, as for [`RiscVDecoder::default`].
+    #[default]
+    X64,
+}
+
+/// Why an ISA string could not be turned into a [`RiscVDecoder`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IsaStringError {
+    /// The string does not start with `rv32`, `rv64` or a known profile name.
+    InvalidBase,
+    /// The base ISA is not one this crate decodes, such as RV32E or RV128I.
+    UnsupportedBase,
+    /// An extension this crate does not know about.
+    UnknownExtension(String),
+}
+
+impl fmt::Display for IsaStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IsaStringError::InvalidBase => write!(f, "invalid base ISA"),
+            IsaStringError::UnsupportedBase => write!(f, "unsupported base ISA"),
+            IsaStringError::UnknownExtension(name) => write!(f, "unknown extension `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for IsaStringError {}
+
+const RVA20U64: &[Extension] = &[
+    Extension::I,
+    Extension::M,
+    Extension::A,
+    Extension::F,
+    Extension::D,
+    Extension::C,
+    Extension::Zicsr,
+    Extension::Zicntr,
+    Extension::Ziccif,
+    Extension::Ziccrse,
+    Extension::Ziccamoa,
+    Extension::Za128rs,
+    Extension::Zicclsm,
+];
+
+const RVA22U64: &[Extension] = &[
+    Extension::I,
+    Extension::M,
+    Extension::A,
+    Extension::F,
+    Extension::D,
+    Extension::C,
+    Extension::Zicsr,
+    Extension::Zicntr,
+    Extension::Zihpm,
+    Extension::Ziccif,
+    Extension::Ziccrse,
+    Extension::Ziccamoa,
+    Extension::Zicclsm,
+    Extension::Za64rs,
+    Extension::Zihintpause,
+    Extension::Zba,
+    Extension::Zbb,
+    Extension::Zbs,
+    Extension::Zic64b,
+    Extension::Zicbom,
+    Extension::Zicbop,
+    Extension::Zicboz,
+    Extension::Zfhmin,
+    Extension::Zkt,
+];
+
+/// Extensions mandated by RVA23U64 on top of RVA22U64.
+const RVA23U64_ADDITIONS: &[Extension] = &[
+    Extension::V,
+    Extension::Zvfhmin,
+    Extension::Zvbb,
+    Extension::Zvkt,
+    Extension::Zihintntl,
+    Extension::Zicond,
+    Extension::Zimop,
+    Extension::Zcmop,
+    Extension::Zcb,
+    Extension::Zfa,
+    Extension::Zawrs,
+    Extension::Supm,
+];
+
+/// Split a trailing version number (`2`, `2p1`) off of an extension name.
+fn strip_version(name: &str) -> &str {
+    let without_minor = match name.rfind('p') {
+        Some(p)
+            if p + 1 < name.len()
+                && name[p + 1..].bytes().all(|b| b.is_ascii_digit())
+                && name[..p].ends_with(|c: char| c.is_ascii_digit()) =>
+        {
+            &name[..p]
+        }
+        _ => name,
+    };
+    let stripped = without_minor.trim_end_matches(|c: char| c.is_ascii_digit());
+    if stripped.is_empty() {
+        name
+    } else {
+        stripped
+    }
+}
+
+fn is_zvl(name: &str) -> bool {
+    name.strip_prefix("zvl")
+        .and_then(|n| n.strip_suffix('b'))
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Parse the single-letter extensions that follow the base ISA in an ISA string.
+fn parse_single_letter(letters: &str, extensions: &mut Extensions) -> Result<(), IsaStringError> {
+    let bytes = letters.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        i += 1;
+
+        // skip any version number, `2` or `2p1`
+        let digits = bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+        i += digits;
+        if digits > 0 && bytes.get(i) == Some(&b'p') {
+            i += 1;
+            i += bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+        }
+
+        if c == 'g' {
+            for ext in [
+                Extension::I,
+                Extension::M,
+                Extension::A,
+                Extension::F,
+                Extension::D,
+                Extension::Zicsr,
+                Extension::Zifencei,
+            ] {
+                extensions.insert(ext);
+            }
+            continue;
+        }
+
+        match Extension::from_name(c.encode_utf8(&mut [0; 4])) {
+            Some(ext) => extensions.insert(ext),
+            None => return Err(IsaStringError::UnknownExtension(c.to_string())),
+        }
+    }
+
+    Ok(())
+}
+
+impl RiscVDecoder {
+    /// Construct a decoder for exactly the extensions named by `isa`, which is either an ISA
+    /// string such as `rv64imafdc_zicsr_zifencei_zba_zbb`, or a profile name such as `rva22u64`
+    /// optionally followed by further `_`-separated extensions.
+    ///
+    /// Version numbers in the ISA string are accepted and ignored. Extensions implied by those
+    /// named, such as F by D, are enabled too.
+    pub fn from_isa_string(isa: &str) -> Result<RiscVDecoder, IsaStringError> {
+        let isa = isa.trim().to_ascii_lowercase();
+        let mut parts = isa.split('_');
+        let base = parts.next().unwrap_or("");
+
+        let mut extensions = Extensions::new();
+        let xlen = match base {
+            "rvi20u32" => {
+                extensions.insert(Extension::I);
+                Xlen::X32
+            }
+            "rvi20u64" => {
+                extensions.insert(Extension::I);
+                Xlen::X64
+            }
+            "rva20u64" => {
+                extensions = RVA20U64.iter().copied().collect();
+                Xlen::X64
+            }
+            "rva22u64" => {
+                extensions = RVA22U64.iter().copied().collect();
+                Xlen::X64
+            }
+            "rva23u64" => {
+                extensions = RVA22U64.iter().chain(RVA23U64_ADDITIONS).copied().collect();
+                Xlen::X64
+            }
+            _ => {
+                let (xlen, rest) = if let Some(rest) = base.strip_prefix("rv32") {
+                    (Xlen::X32, rest)
+                } else if let Some(rest) = base.strip_prefix("rv64") {
+                    (Xlen::X64, rest)
+                } else if base.starts_with("rv128") {
+                    return Err(IsaStringError::UnsupportedBase);
+                } else {
+                    return Err(IsaStringError::InvalidBase);
+                };
+
+                match rest.chars().next() {
+                    Some('i') | Some('g') => {}
+                    Some('e') => return Err(IsaStringError::UnsupportedBase),
+                    _ => return Err(IsaStringError::InvalidBase),
+                }
+
+                parse_single_letter(rest, &mut extensions)?;
+                xlen
+            }
+        };
+
+        for part in parts.filter(|part| !part.is_empty()) {
+            let name = strip_version(part);
+            match Extension::from_name(name) {
+                Some(ext) => extensions.insert(ext),
+                // Zvl<N>b constrains VLEN, and adds no instructions
+                None if is_zvl(name) => {}
+                None => return Err(IsaStringError::UnknownExtension(name.to_string())),
+            }
+        }
+
+        extensions.close_over_implications();
+        // C also includes the compressed floating-point loads and stores of the enabled F and D
+        if extensions.contains(Extension::C) {
+            if extensions.contains(Extension::D) {
+                extensions.insert(Extension::Zcd);
+            }
+            if extensions.contains(Extension::F) && xlen == Xlen::X32 {
+                extensions.insert(Extension::Zcf);
+            }
+        }
+
+        Ok(RiscVDecoder {
+            xlen,
+            extensions,
+            custom: None,
+        })
+    }
+}
+
+impl Opcode {
+    /// Every extension that must be implemented for this opcode to be valid.
+    ///
+    /// This is empty for [`Opcode::Invalid`], [`Opcode::Opaque`] and [`Opcode::Custom`].
+    pub fn extensions(&self) -> Extensions {
+        let mut required: Extensions = self.extension().into_iter().collect();
+        match self {
+            Opcode::HINVAL_VVMA | Opcode::HINVAL_GVMA => required.insert(Extension::H),
+            // indexed loads and stores of floating-point registers
+            Opcode::TH_FLRW | Opcode::TH_FLURW | Opcode::TH_FSRW | Opcode::TH_FSURW => {
+                required.insert(Extension::F)
+            }
+            Opcode::TH_FLRD | Opcode::TH_FLURD | Opcode::TH_FSRD | Opcode::TH_FSURD => {
+                required.insert(Extension::D)
+            }
+            _ => {}
+        }
+        required
+    }
+
+    /// The extension this opcode is defined by, if any.
+    fn extension(&self) -> Option<Extension> {
+        match self {
+            Opcode::Invalid | Opcode::Custom(_) | Opcode::Opaque => None,
+            Opcode::LUI
+            | Opcode::AUIPC
+            | Opcode::JAL
+            | Opcode::JALR
+            | Opcode::BEQ
+            | Opcode::BNE
+            | Opcode::BLT
+            | Opcode::BGE
+            | Opcode::BLTU
+            | Opcode::BGEU
+            | Opcode::LB
+            | Opcode::LH
+            | Opcode::LW
+            | Opcode::LBU
+            | Opcode::LHU
+            | Opcode::SB
+            | Opcode::SH
+            | Opcode::SW
+            | Opcode::ADDI
+            | Opcode::SLTI
+            | Opcode::SLTIU
+            | Opcode::XORI
+            | Opcode::ORI
+            | Opcode::ANDI
+            | Opcode::SLLI
+            | Opcode::SRLI
+            | Opcode::SRAI
+            | Opcode::ADD
+            | Opcode::SUB
+            | Opcode::SLL
+            | Opcode::SLT
+            | Opcode::SLTU
+            | Opcode::XOR
+            | Opcode::SRL
+            | Opcode::SRA
+            | Opcode::OR
+            | Opcode::AND
+            | Opcode::FENCE
+            | Opcode::ECALL
+            | Opcode::EBREAK
+            | Opcode::LWU
+            | Opcode::LD
+            | Opcode::SD
+            | Opcode::ADDIW
+            | Opcode::SLLIW
+            | Opcode::SRLIW
+            | Opcode::SRAIW
+            | Opcode::ADDW
+            | Opcode::SUBW
+            | Opcode::SLLW
+            | Opcode::SRLW
+            | Opcode::SRAW
+            | Opcode::FENCE_TSO => Some(Extension::I),
+            Opcode::FENCE_I => Some(Extension::Zifencei),
+            Opcode::CSRRW
+            | Opcode::CSRRS
+            | Opcode::CSRRC
+            | Opcode::CSRRWI
+            | Opcode::CSRRSI
+            | Opcode::CSRRCI => Some(Extension::Zicsr),
+            Opcode::FSGNJ_S | Opcode::FSGNJN_S | Opcode::FSGNJX_S => Some(Extension::F),
+            Opcode::FSGNJ_D | Opcode::FSGNJN_D | Opcode::FSGNJX_D => Some(Extension::D),
+            Opcode::PAUSE => Some(Extension::Zihintpause),
+            Opcode::FCVT_BF16_S | Opcode::FCVT_S_BF16 => Some(Extension::Zfbfmin),
+            Opcode::VFNCVTBF16_F_F_W | Opcode::VFWCVTBF16_F_F_V => Some(Extension::Zvfbfmin),
+            Opcode::VFWMACCBF16_VV | Opcode::VFWMACCBF16_VF => Some(Extension::Zvfbfwma),
+            Opcode::SINVAL_VMA
+            | Opcode::SFENCE_W_INVAL
+            | Opcode::SFENCE_INVAL_IR
+            | Opcode::HINVAL_VVMA
+            | Opcode::HINVAL_GVMA => Some(Extension::Svinval),
+            Opcode::ADD16
+            | Opcode::RADD16
+            | Opcode::URADD16
+            | Opcode::KADD16
+            | Opcode::UKADD16
+            | Opcode::SUB16
+            | Opcode::RSUB16
+            | Opcode::URSUB16
+            | Opcode::KSUB16
+            | Opcode::UKSUB16
+            | Opcode::CRAS16
+            | Opcode::RCRAS16
+            | Opcode::KCRAS16
+            | Opcode::CRSA16
+            | Opcode::RCRSA16
+            | Opcode::KCRSA16
+            | Opcode::ADD8
+            | Opcode::RADD8
+            | Opcode::URADD8
+            | Opcode::KADD8
+            | Opcode::UKADD8
+            | Opcode::SUB8
+            | Opcode::RSUB8
+            | Opcode::URSUB8
+            | Opcode::KSUB8
+            | Opcode::UKSUB8
+            | Opcode::SRA16
+            | Opcode::SRL16
+            | Opcode::SLL16
+            | Opcode::SRA8
+            | Opcode::SRL8
+            | Opcode::SLL8
+            | Opcode::CMPEQ16
+            | Opcode::SCMPLT16
+            | Opcode::SCMPLE16
+            | Opcode::UCMPLT16
+            | Opcode::UCMPLE16
+            | Opcode::CMPEQ8
+            | Opcode::SCMPLT8
+            | Opcode::SCMPLE8
+            | Opcode::UCMPLT8
+            | Opcode::UCMPLE8
+            | Opcode::SMIN16
+            | Opcode::UMIN16
+            | Opcode::SMAX16
+            | Opcode::UMAX16
+            | Opcode::SMIN8
+            | Opcode::UMIN8
+            | Opcode::SMAX8
+            | Opcode::UMAX8
+            | Opcode::KHM16
+            | Opcode::KHMX16
+            | Opcode::KHM8
+            | Opcode::KHMX8
+            | Opcode::SMAQA
+            | Opcode::UMAQA
+            | Opcode::SMAQA_SU
+            | Opcode::PKBB16
+            | Opcode::PKBT16
+            | Opcode::PKTB16
+            | Opcode::PKTT16 => Some(Extension::P),
+            Opcode::TH_ADDSL => Some(Extension::XTheadBa),
+            Opcode::TH_SRRI
+            | Opcode::TH_SRRIW
+            | Opcode::TH_EXT
+            | Opcode::TH_EXTU
+            | Opcode::TH_FF0
+            | Opcode::TH_FF1
+            | Opcode::TH_REV
+            | Opcode::TH_REVW
+            | Opcode::TH_TSTNBZ => Some(Extension::XTheadBb),
+            Opcode::TH_TST => Some(Extension::XTheadBs),
+            Opcode::TH_MVEQZ | Opcode::TH_MVNEZ => Some(Extension::XTheadCondMov),
+            Opcode::TH_MULA
+            | Opcode::TH_MULAH
+            | Opcode::TH_MULAW
+            | Opcode::TH_MULS
+            | Opcode::TH_MULSH
+            | Opcode::TH_MULSW => Some(Extension::XTheadMac),
+            Opcode::TH_LBIA
+            | Opcode::TH_LBIB
+            | Opcode::TH_LHIA
+            | Opcode::TH_LHIB
+            | Opcode::TH_LWIA
+            | Opcode::TH_LWIB
+            | Opcode::TH_LDIA
+            | Opcode::TH_LDIB
+            | Opcode::TH_LBUIA
+            | Opcode::TH_LBUIB
+            | Opcode::TH_LHUIA
+            | Opcode::TH_LHUIB
+            | Opcode::TH_LWUIA
+            | Opcode::TH_LWUIB
+            | Opcode::TH_LRB
+            | Opcode::TH_LRH
+            | Opcode::TH_LRW
+            | Opcode::TH_LRD
+            | Opcode::TH_LRBU
+            | Opcode::TH_LRHU
+            | Opcode::TH_LRWU
+            | Opcode::TH_LURB
+            | Opcode::TH_LURH
+            | Opcode::TH_LURW
+            | Opcode::TH_LURD
+            | Opcode::TH_LURBU
+            | Opcode::TH_LURHU
+            | Opcode::TH_LURWU
+            | Opcode::TH_SBIA
+            | Opcode::TH_SBIB
+            | Opcode::TH_SHIA
+            | Opcode::TH_SHIB
+            | Opcode::TH_SWIA
+            | Opcode::TH_SWIB
+            | Opcode::TH_SDIA
+            | Opcode::TH_SDIB
+            | Opcode::TH_SRB
+            | Opcode::TH_SRH
+            | Opcode::TH_SRW
+            | Opcode::TH_SRD
+            | Opcode::TH_SURB
+            | Opcode::TH_SURH
+            | Opcode::TH_SURW
+            | Opcode::TH_SURD => Some(Extension::XTheadMemIdx),
+            Opcode::TH_LWD | Opcode::TH_LWUD | Opcode::TH_LDD | Opcode::TH_SWD | Opcode::TH_SDD => {
+                Some(Extension::XTheadMemPair)
+            }
+            Opcode::TH_FLRW
+            | Opcode::TH_FLRD
+            | Opcode::TH_FLURW
+            | Opcode::TH_FLURD
+            | Opcode::TH_FSRW
+            | Opcode::TH_FSRD
+            | Opcode::TH_FSURW
+            | Opcode::TH_FSURD => Some(Extension::XTheadFMemIdx),
+            Opcode::TH_SYNC
+            | Opcode::TH_SYNC_S
+            | Opcode::TH_SYNC_I
+            | Opcode::TH_SYNC_IS
+            | Opcode::TH_SFENCE_VMAS => Some(Extension::XTheadSync),
+            Opcode::TH_DCACHE_CALL
+            | Opcode::TH_DCACHE_IALL
+            | Opcode::TH_DCACHE_CIALL
+            | Opcode::TH_DCACHE_CSW
+            | Opcode::TH_DCACHE_ISW
+            | Opcode::TH_DCACHE_CISW
+            | Opcode::TH_DCACHE_CVAL1
+            | Opcode::TH_DCACHE_CVA
+            | Opcode::TH_DCACHE_IVA
+            | Opcode::TH_DCACHE_CIVA
+            | Opcode::TH_DCACHE_CPAL1
+            | Opcode::TH_DCACHE_CPA
+            | Opcode::TH_DCACHE_IPA
+            | Opcode::TH_DCACHE_CIPA
+            | Opcode::TH_ICACHE_IALL
+            | Opcode::TH_ICACHE_IALLS
+            | Opcode::TH_ICACHE_IVA
+            | Opcode::TH_ICACHE_IPA
+            | Opcode::TH_L2CACHE_CALL
+            | Opcode::TH_L2CACHE_IALL
+            | Opcode::TH_L2CACHE_CIALL => Some(Extension::XTheadCmo),
+            Opcode::CV_STARTI
+            | Opcode::CV_START
+            | Opcode::CV_ENDI
+            | Opcode::CV_END
+            | Opcode::CV_COUNTI
+            | Opcode::CV_COUNT
+            | Opcode::CV_SETUPI
+            | Opcode::CV_SETUP => Some(Extension::XCVhwlp),
+            Opcode::CV_LB
+            | Opcode::CV_LBU
+            | Opcode::CV_LH
+            | Opcode::CV_LHU
+            | Opcode::CV_LW
+            | Opcode::CV_SB
+            | Opcode::CV_SH
+            | Opcode::CV_SW => Some(Extension::XCVmem),
+            Opcode::CV_MAC | Opcode::CV_MSU => Some(Extension::XCVmac),
+            Opcode::CV_ABS
+            | Opcode::CV_SLET
+            | Opcode::CV_SLETU
+            | Opcode::CV_MIN
+            | Opcode::CV_MINU
+            | Opcode::CV_MAX
+            | Opcode::CV_MAXU
+            | Opcode::CV_EXTHS
+            | Opcode::CV_EXTHZ
+            | Opcode::CV_EXTBS
+            | Opcode::CV_EXTBZ
+            | Opcode::CV_CLIP
+            | Opcode::CV_CLIPU
+            | Opcode::CV_CLIPR
+            | Opcode::CV_CLIPUR => Some(Extension::XCValu),
+            Opcode::CV_ADD_H
+            | Opcode::CV_ADD_B
+            | Opcode::CV_ADD_SC_H
+            | Opcode::CV_ADD_SC_B
+            | Opcode::CV_SUB_H
+            | Opcode::CV_SUB_B
+            | Opcode::CV_SUB_SC_H
+            | Opcode::CV_SUB_SC_B
+            | Opcode::CV_AVG_H
+            | Opcode::CV_AVG_B
+            | Opcode::CV_AVG_SC_H
+            | Opcode::CV_AVG_SC_B
+            | Opcode::CV_AVGU_H
+            | Opcode::CV_AVGU_B
+            | Opcode::CV_AVGU_SC_H
+            | Opcode::CV_AVGU_SC_B
+            | Opcode::CV_MIN_H
+            | Opcode::CV_MIN_B
+            | Opcode::CV_MIN_SC_H
+            | Opcode::CV_MIN_SC_B
+            | Opcode::CV_MINU_H
+            | Opcode::CV_MINU_B
+            | Opcode::CV_MINU_SC_H
+            | Opcode::CV_MINU_SC_B
+            | Opcode::CV_MAX_H
+            | Opcode::CV_MAX_B
+            | Opcode::CV_MAX_SC_H
+            | Opcode::CV_MAX_SC_B
+            | Opcode::CV_MAXU_H
+            | Opcode::CV_MAXU_B
+            | Opcode::CV_MAXU_SC_H
+            | Opcode::CV_MAXU_SC_B
+            | Opcode::CV_SRL_H
+            | Opcode::CV_SRL_B
+            | Opcode::CV_SRL_SC_H
+            | Opcode::CV_SRL_SC_B
+            | Opcode::CV_SRA_H
+            | Opcode::CV_SRA_B
+            | Opcode::CV_SRA_SC_H
+            | Opcode::CV_SRA_SC_B
+            | Opcode::CV_SLL_H
+            | Opcode::CV_SLL_B
+            | Opcode::CV_SLL_SC_H
+            | Opcode::CV_SLL_SC_B
+            | Opcode::CV_OR_H
+            | Opcode::CV_OR_B
+            | Opcode::CV_OR_SC_H
+            | Opcode::CV_OR_SC_B
+            | Opcode::CV_XOR_H
+            | Opcode::CV_XOR_B
+            | Opcode::CV_XOR_SC_H
+            | Opcode::CV_XOR_SC_B
+            | Opcode::CV_AND_H
+            | Opcode::CV_AND_B
+            | Opcode::CV_AND_SC_H
+            | Opcode::CV_AND_SC_B
+            | Opcode::CV_ABS_H
+            | Opcode::CV_ABS_B => Some(Extension::XCVsimd),
+        }
+    }
+}
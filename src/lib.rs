@@ -1,27 +1,52 @@
+use std::fmt;
+
 #[cfg(feature = "use-serde")]
 use serde::{Deserialize, Serialize};
 
-use yaxpeax_arch::{AddressDiff, Arch, Decoder, LengthedInstruction, Reader, StandardDecodeError};
+use yaxpeax_arch::{AddressDiff, Arch, Decoder, LengthedInstruction, ReadError, Reader};
+
+pub use attributes::{arch_attribute, AttributesError};
+pub use custom::{CustomDecoder, CustomInstruction, CustomSpace};
+pub use display::{
+    BufferSink, ColorSink, DisplayContext, DisplayOptions, DisplaySink, InstructionDisplay,
+    Palette, Radix, Register, RegisterNames, Signedness, Style, Symbolizer, Syntax, Token,
+    TokenKind, UpperImmediate,
+};
+pub use extension::{Extension, Extensions, IsaStringError, Xlen};
+pub use listing::{Listing, ListingLine};
 
+mod attributes;
+mod corev;
+mod custom;
 mod display;
+mod extension;
+mod listing;
+mod packed;
+mod thead;
 
 #[derive(Debug, PartialEq)]
 pub struct Instruction {
     word: u32,
-    operands: [OperandSpec; 3],
+    /// Parcels past the first two of an instruction longer than 32 bits, up to the nine more of a
+    /// 176-bit instruction.
+    parcels_hi: [u16; 9],
+    /// Length of the instruction in bytes.
+    length: u8,
+    operands: [OperandSpec; 5],
     opcode: Opcode,
+    /// Operands of an instruction decoded by a [`CustomDecoder`].
+    custom_operands: [Option<Operand>; 5],
 }
 
 impl Default for Instruction {
     fn default() -> Self {
         Instruction {
             word: 0,
-            operands: [
-                OperandSpec::Nothing,
-                OperandSpec::Nothing,
-                OperandSpec::Nothing,
-            ],
+            parcels_hi: [0; 9],
+            length: 4,
+            operands: [OperandSpec::Nothing; 5],
             opcode: Opcode::Invalid,
+            custom_operands: [None; 5],
         }
     }
 }
@@ -32,7 +57,7 @@ impl Instruction {
             FieldSpec::Rs1 => (self.word >> 15) & 0b11111,
             FieldSpec::Rs2 => (self.word >> 20) & 0b11111,
             FieldSpec::Rd => (self.word >> 7) & 0b11111,
-            FieldSpec::Shamt => (self.word >> 20) & 0b11111,
+            FieldSpec::Shamt => (self.word >> 20) & 0b11_1111,
             FieldSpec::Imm12I => (self.word as i32 >> 20) as u32,
             FieldSpec::Imm12S => {
                 let a = ((self.word >> 7) & 0b11111) as i32;
@@ -48,8 +73,26 @@ impl Instruction {
 
                 (a | b | c) as u32
             }
-            FieldSpec::Imm20U => (self.word & 0xFFFF_F000u32) as u32,
-            FieldSpec::Imm20J => todo!(),
+            FieldSpec::Imm20U => self.word & 0xFFFF_F000u32,
+            FieldSpec::Imm20J => {
+                let a = (self.word >> 20) & 0b111_1111_1110;
+                let b = (self.word >> 9) & 0b1000_0000_0000;
+                let c = self.word & 0b1111_1111_0000_0000_0000;
+                let d = ((self.word as i32) >> 11) as u32 & 0xfff0_0000;
+
+                a | b | c | d
+            }
+            FieldSpec::Rm => (self.word >> 12) & 0b111,
+            FieldSpec::Vm => (self.word >> 25) & 0b1,
+            FieldSpec::Imm2 => (self.word >> 25) & 0b11,
+            FieldSpec::Uimm5 => (self.word >> 20) & 0b1_1111,
+            FieldSpec::Uimm6 => (self.word >> 20) & 0b11_1111,
+            FieldSpec::Simm5 => ((self.word as i32) << 7 >> 27) as u32,
+            FieldSpec::Msb6 => (self.word >> 26) & 0b11_1111,
+            FieldSpec::Uimm12 => self.word >> 20,
+            FieldSpec::Csr => self.word >> 20,
+            FieldSpec::Pred => (self.word >> 24) & 0b1111,
+            FieldSpec::Succ => (self.word >> 20) & 0b1111,
         }
     }
 
@@ -59,12 +102,12 @@ impl Instruction {
             OperandSpec::Rs1 => Some(Operand::Reg(self.field(FieldSpec::Rs1) as u8)),
             OperandSpec::Rs2 => Some(Operand::Reg(self.field(FieldSpec::Rs2) as u8)),
             OperandSpec::Rd => Some(Operand::Reg(self.field(FieldSpec::Rd) as u8)),
-            OperandSpec::Shamt => Some(Operand::Shift(self.field(FieldSpec::Imm12I) as u8)),
+            OperandSpec::Shamt => Some(Operand::Shift(self.field(FieldSpec::Shamt) as u8)),
             OperandSpec::Imm12I => Some(Operand::Imm(self.field(FieldSpec::Imm12I) as i32)),
             OperandSpec::Imm12S => Some(Operand::Imm(self.field(FieldSpec::Imm12S) as i32)),
             OperandSpec::Imm12B => Some(Operand::JOffset(self.field(FieldSpec::Imm12B) as i32)),
             OperandSpec::Imm20U => Some(Operand::Imm(self.field(FieldSpec::Imm20U) as i32)),
-            OperandSpec::Imm20J => todo!(),
+            OperandSpec::Imm20J => Some(Operand::JOffset(self.field(FieldSpec::Imm20J) as i32)),
             OperandSpec::BaseOffsetRs1I => Some(Operand::BaseOffset(
                 self.field(FieldSpec::Rs1) as u8,
                 self.field(FieldSpec::Imm12I) as i16,
@@ -73,6 +116,72 @@ impl Instruction {
                 self.field(FieldSpec::Rs1) as u8,
                 self.field(FieldSpec::Imm12S) as i16,
             )),
+            OperandSpec::FRs1 => Some(Operand::FReg(self.field(FieldSpec::Rs1) as u8)),
+            OperandSpec::FRs2 => Some(Operand::FReg(self.field(FieldSpec::Rs2) as u8)),
+            OperandSpec::FRd => Some(Operand::FReg(self.field(FieldSpec::Rd) as u8)),
+            OperandSpec::VRs1 => Some(Operand::VReg(self.field(FieldSpec::Rs1) as u8)),
+            OperandSpec::VRs2 => Some(Operand::VReg(self.field(FieldSpec::Rs2) as u8)),
+            OperandSpec::VRd => Some(Operand::VReg(self.field(FieldSpec::Rd) as u8)),
+            OperandSpec::Rm => match self.field(FieldSpec::Rm) {
+                0b000 => Some(Operand::RoundingMode(RoundingMode::RNE)),
+                0b001 => Some(Operand::RoundingMode(RoundingMode::RTZ)),
+                0b010 => Some(Operand::RoundingMode(RoundingMode::RDN)),
+                0b011 => Some(Operand::RoundingMode(RoundingMode::RUP)),
+                0b100 => Some(Operand::RoundingMode(RoundingMode::RMM)),
+                // dynamic rounding mode is implied when the operand is absent
+                _ => None,
+            },
+            OperandSpec::Vm => match self.field(FieldSpec::Vm) {
+                0 => Some(Operand::VMask),
+                _ => None,
+            },
+            OperandSpec::BaseRs1 => Some(Operand::Base(self.field(FieldSpec::Rs1) as u8)),
+            OperandSpec::Imm2 => Some(Operand::Imm(self.field(FieldSpec::Imm2) as i32)),
+            OperandSpec::Uimm5 => Some(Operand::Imm(self.field(FieldSpec::Uimm5) as i32)),
+            OperandSpec::Uimm6 => Some(Operand::Imm(self.field(FieldSpec::Uimm6) as i32)),
+            OperandSpec::Simm5 => Some(Operand::Imm(self.field(FieldSpec::Simm5) as i32)),
+            OperandSpec::Msb6 => Some(Operand::Imm(self.field(FieldSpec::Msb6) as i32)),
+            OperandSpec::Uimm12 => Some(Operand::Imm(self.field(FieldSpec::Uimm12) as i32)),
+            OperandSpec::Csr => Some(Operand::Csr(self.field(FieldSpec::Csr) as u16)),
+            OperandSpec::Zimm => Some(Operand::Imm(self.field(FieldSpec::Rs1) as i32)),
+            OperandSpec::Pred => Some(Operand::FenceSet(self.field(FieldSpec::Pred) as u8)),
+            OperandSpec::Succ => Some(Operand::FenceSet(self.field(FieldSpec::Succ) as u8)),
+            OperandSpec::PostIncRs1I => Some(Operand::PostIncrement(
+                self.field(FieldSpec::Rs1) as u8,
+                self.field(FieldSpec::Imm12I) as i16,
+            )),
+            OperandSpec::PostIncRs1S => Some(Operand::PostIncrement(
+                self.field(FieldSpec::Rs1) as u8,
+                self.field(FieldSpec::Imm12S) as i16,
+            )),
+            OperandSpec::PostIncRs1Rs2 => Some(Operand::PostIncrementReg(
+                self.field(FieldSpec::Rs1) as u8,
+                self.field(FieldSpec::Rs2) as u8,
+            )),
+            OperandSpec::PostIncRs1Rd => Some(Operand::PostIncrementReg(
+                self.field(FieldSpec::Rs1) as u8,
+                self.field(FieldSpec::Rd) as u8,
+            )),
+            OperandSpec::BaseIndexRs1Rs2 => Some(Operand::BaseIndex(
+                self.field(FieldSpec::Rs1) as u8,
+                self.field(FieldSpec::Rs2) as u8,
+            )),
+            OperandSpec::BaseIndexRs1Rd => Some(Operand::BaseIndex(
+                self.field(FieldSpec::Rs1) as u8,
+                self.field(FieldSpec::Rd) as u8,
+            )),
+            OperandSpec::LoopL => Some(Operand::Imm(((self.word >> 7) & 0b1) as i32)),
+            OperandSpec::LoopOffset12 => Some(Operand::JOffset(
+                (self.field(FieldSpec::Uimm12) << 1) as i32,
+            )),
+            OperandSpec::LoopOffset5 => {
+                Some(Operand::JOffset((self.field(FieldSpec::Rs1) << 1) as i32))
+            }
+            OperandSpec::Custom(idx) => self.custom_operands[*idx as usize],
+            OperandSpec::PairShift => match self.opcode {
+                Opcode::TH_LDD | Opcode::TH_SDD => Some(Operand::Imm(4)),
+                _ => Some(Operand::Imm(3)),
+            },
         }
     }
 
@@ -80,19 +189,150 @@ impl Instruction {
         &self.opcode
     }
 
+    /// The address this branch or `JAL` jumps to when it is at `address`.
+    ///
+    /// `None` for any other instruction, including `JALR`, whose target depends on a register.
+    /// The address wraps around at 64 bits; on RV32 only its low 32 bits are meaningful.
+    pub fn branch_target(&self, address: u64) -> Option<u64> {
+        let offset = match self.opcode {
+            Opcode::JAL => self.field(FieldSpec::Imm20J),
+            Opcode::BEQ | Opcode::BNE | Opcode::BLT | Opcode::BGE | Opcode::BLTU | Opcode::BGEU => {
+                self.field(FieldSpec::Imm12B)
+            }
+            _ => return None,
+        };
+        Some(address.wrapping_add(offset as i32 as i64 as u64))
+    }
+
+    /// Operand `index` of this instruction, without collecting them all as
+    /// [`Instruction::operands`] does.
+    pub fn operand_at(&self, index: usize) -> Option<Operand> {
+        self.operands.get(index).and_then(|op| self.operand(op))
+    }
+
     pub fn operands(&self) -> Vec<Option<Operand>> {
-        self.operands.iter().map(|o| self.operand(o)).collect::<Vec<_>>()
+        self.operands
+            .iter()
+            .map(|o| self.operand(o))
+            .collect::<Vec<_>>()
+    }
+
+    /// Every extension that must be implemented for this instruction to be valid.
+    ///
+    /// See [`Opcode::extensions`].
+    pub fn extensions(&self) -> Extensions {
+        self.opcode.extensions()
     }
 
     pub fn word(&self) -> &u32 {
         &self.word
     }
+
+    /// The first 64 bits of the instruction, for instructions longer than 32 bits.
+    ///
+    /// See [`Instruction::parcel`] for the rest of an instruction longer than 64 bits.
+    pub fn long_word(&self) -> u64 {
+        let hi = ((self.parcels_hi[1] as u64) << 16) | self.parcels_hi[0] as u64;
+        (hi << 32) | self.word as u64
+    }
+
+    /// The 16-bit parcel at `index` of the instruction, counting from the first, or `None` past
+    /// the instruction's end.
+    pub fn parcel(&self, index: usize) -> Option<u16> {
+        if index >= self.length as usize / 2 {
+            return None;
+        }
+        Some(match index {
+            0 => self.word as u16,
+            1 => (self.word >> 16) as u16,
+            _ => self.parcels_hi[index - 2],
+        })
+    }
+}
+
+impl Instruction {
+    /// The class of HINT this instruction is, if it is one.
+    ///
+    /// HINTs are encodings of ordinary instructions that have no architecturally visible effect,
+    /// such as those writing `zero`, which the ISA sets aside to carry performance hints.
+    pub fn hint(&self) -> Option<HintClass> {
+        let rd = self.field(FieldSpec::Rd);
+        let rs1 = self.field(FieldSpec::Rs1);
+        let rs2 = self.field(FieldSpec::Rs2);
+        let imm = self.field(FieldSpec::Imm12I);
+
+        match self.opcode {
+            Opcode::PAUSE => Some(HintClass::Pause),
+            Opcode::FENCE
+                if self.field(FieldSpec::Pred) == 0 || self.field(FieldSpec::Succ) == 0 =>
+            {
+                Some(HintClass::Standard)
+            }
+            // Zicbop prefetch.i, prefetch.r and prefetch.w
+            Opcode::ORI if rd == 0 && matches!(imm & 0b1_1111, 0b00000 | 0b00001 | 0b00011) => {
+                Some(HintClass::Prefetch)
+            }
+            // Zihintntl ntl.p1, ntl.pall, ntl.s1 and ntl.all
+            Opcode::ADD if rd == 0 && rs1 == 0 && (2..=5).contains(&rs2) => {
+                Some(HintClass::NonTemporal)
+            }
+            // `addi zero, zero, 0` is nop
+            Opcode::ADDI if rd == 0 && rs1 == 0 && imm == 0 => None,
+            Opcode::LUI
+            | Opcode::AUIPC
+            | Opcode::ADDI
+            | Opcode::ANDI
+            | Opcode::ORI
+            | Opcode::XORI
+            | Opcode::ADD
+            | Opcode::SUB
+            | Opcode::AND
+            | Opcode::OR
+            | Opcode::XOR
+            | Opcode::SLL
+            | Opcode::SRL
+            | Opcode::SRA
+                if rd == 0 =>
+            {
+                Some(HintClass::Standard)
+            }
+            Opcode::SLTI
+            | Opcode::SLTIU
+            | Opcode::SLLI
+            | Opcode::SRLI
+            | Opcode::SRAI
+            | Opcode::SLT
+            | Opcode::SLTU
+                if rd == 0 =>
+            {
+                Some(HintClass::Custom)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl yaxpeax_arch::Instruction for Instruction {
+    /// `false` for encodings whose behaviour the ISA reserves, but which implementations may
+    /// still execute.
     fn well_defined(&self) -> bool {
-        // TODO: this is inaccurate
-        true
+        let rd = self.field(FieldSpec::Rd);
+        let rs1 = self.field(FieldSpec::Rs1);
+        let rs2 = self.field(FieldSpec::Rs2);
+        let masked = self.field(FieldSpec::Vm) == 0;
+
+        match self.opcode {
+            // fields reserved for finer-grained fences, which implementations ignore
+            Opcode::FENCE => rd == 0 && rs1 == 0 && self.word >> 28 == 0,
+            Opcode::FENCE_TSO => rd == 0 && rs1 == 0,
+            Opcode::FENCE_I => self.word >> 15 == 0 && rd == 0,
+            // masked vector operations may not overwrite the mask, and widening operations may
+            // not overwrite a narrower source
+            Opcode::VFNCVTBF16_F_F_W => !(masked && rd == 0),
+            Opcode::VFWCVTBF16_F_F_V | Opcode::VFWMACCBF16_VF => !(masked && rd == 0) && rd != rs2,
+            Opcode::VFWMACCBF16_VV => !(masked && rd == 0) && rd != rs2 && rd != rs1,
+            _ => true,
+        }
     }
 }
 
@@ -103,14 +343,22 @@ impl LengthedInstruction for Instruction {
     }
 
     fn len(&self) -> Self::Unit {
-        AddressDiff::from_const(4)
+        AddressDiff::from_const(self.length as u32)
     }
 }
 
+#[allow(non_camel_case_types)]
 #[derive(Debug, PartialEq)]
 pub enum Opcode {
     Invalid,
 
+    /// An instruction longer than 32 bits, whose length is known but whose contents are not
+    /// decoded.
+    Opaque,
+
+    /// An instruction decoded by a [`CustomDecoder`], with its mnemonic
+    Custom(&'static str),
+
     // RV32I Base Instruction Set
     LUI,
     AUIPC,
@@ -150,6 +398,7 @@ pub enum Opcode {
     OR,
     AND,
     FENCE,
+    FENCE_TSO,
     ECALL,
     EBREAK,
 
@@ -169,6 +418,309 @@ pub enum Opcode {
     SLLW,
     SRLW,
     SRAW,
+
+    // Zifencei Extension
+    FENCE_I,
+
+    // Zicsr Extension
+    CSRRW,
+    CSRRS,
+    CSRRC,
+    CSRRWI,
+    CSRRSI,
+    CSRRCI,
+
+    // Zihintpause Extension
+    PAUSE,
+
+    // F Extension
+    FSGNJ_S,
+    FSGNJN_S,
+    FSGNJX_S,
+
+    // D Extension
+    FSGNJ_D,
+    FSGNJN_D,
+    FSGNJX_D,
+
+    // Zfbfmin Extension
+    FCVT_BF16_S,
+    FCVT_S_BF16,
+
+    // Zvfbfmin Extension
+    VFNCVTBF16_F_F_W,
+    VFWCVTBF16_F_F_V,
+
+    // Zvfbfwma Extension
+    VFWMACCBF16_VV,
+    VFWMACCBF16_VF,
+
+    // Svinval Extension
+    SINVAL_VMA,
+    SFENCE_W_INVAL,
+    SFENCE_INVAL_IR,
+    HINVAL_VVMA,
+    HINVAL_GVMA,
+
+    // P Extension (draft)
+    ADD16,
+    RADD16,
+    URADD16,
+    KADD16,
+    UKADD16,
+    SUB16,
+    RSUB16,
+    URSUB16,
+    KSUB16,
+    UKSUB16,
+    CRAS16,
+    RCRAS16,
+    KCRAS16,
+    CRSA16,
+    RCRSA16,
+    KCRSA16,
+    ADD8,
+    RADD8,
+    URADD8,
+    KADD8,
+    UKADD8,
+    SUB8,
+    RSUB8,
+    URSUB8,
+    KSUB8,
+    UKSUB8,
+    SRA16,
+    SRL16,
+    SLL16,
+    SRA8,
+    SRL8,
+    SLL8,
+    CMPEQ16,
+    SCMPLT16,
+    SCMPLE16,
+    UCMPLT16,
+    UCMPLE16,
+    CMPEQ8,
+    SCMPLT8,
+    SCMPLE8,
+    UCMPLT8,
+    UCMPLE8,
+    SMIN16,
+    UMIN16,
+    SMAX16,
+    UMAX16,
+    SMIN8,
+    UMIN8,
+    SMAX8,
+    UMAX8,
+    KHM16,
+    KHMX16,
+    KHM8,
+    KHMX8,
+    SMAQA,
+    UMAQA,
+    SMAQA_SU,
+    PKBB16,
+    PKBT16,
+    PKTB16,
+    PKTT16,
+
+    // T-Head Vendor Extensions
+    TH_ADDSL,
+    TH_SRRI,
+    TH_SRRIW,
+    TH_EXT,
+    TH_EXTU,
+    TH_FF0,
+    TH_FF1,
+    TH_REV,
+    TH_REVW,
+    TH_TSTNBZ,
+    TH_TST,
+    TH_MVEQZ,
+    TH_MVNEZ,
+    TH_MULA,
+    TH_MULAH,
+    TH_MULAW,
+    TH_MULS,
+    TH_MULSH,
+    TH_MULSW,
+    TH_LBIA,
+    TH_LBIB,
+    TH_LHIA,
+    TH_LHIB,
+    TH_LWIA,
+    TH_LWIB,
+    TH_LDIA,
+    TH_LDIB,
+    TH_LBUIA,
+    TH_LBUIB,
+    TH_LHUIA,
+    TH_LHUIB,
+    TH_LWUIA,
+    TH_LWUIB,
+    TH_LRB,
+    TH_LRH,
+    TH_LRW,
+    TH_LRD,
+    TH_LRBU,
+    TH_LRHU,
+    TH_LRWU,
+    TH_LURB,
+    TH_LURH,
+    TH_LURW,
+    TH_LURD,
+    TH_LURBU,
+    TH_LURHU,
+    TH_LURWU,
+    TH_SBIA,
+    TH_SBIB,
+    TH_SHIA,
+    TH_SHIB,
+    TH_SWIA,
+    TH_SWIB,
+    TH_SDIA,
+    TH_SDIB,
+    TH_SRB,
+    TH_SRH,
+    TH_SRW,
+    TH_SRD,
+    TH_SURB,
+    TH_SURH,
+    TH_SURW,
+    TH_SURD,
+    TH_LWD,
+    TH_LWUD,
+    TH_LDD,
+    TH_SWD,
+    TH_SDD,
+    TH_FLRW,
+    TH_FLRD,
+    TH_FLURW,
+    TH_FLURD,
+    TH_FSRW,
+    TH_FSRD,
+    TH_FSURW,
+    TH_FSURD,
+    TH_SYNC,
+    TH_SYNC_S,
+    TH_SYNC_I,
+    TH_SYNC_IS,
+    TH_SFENCE_VMAS,
+    TH_DCACHE_CALL,
+    TH_DCACHE_IALL,
+    TH_DCACHE_CIALL,
+    TH_DCACHE_CSW,
+    TH_DCACHE_ISW,
+    TH_DCACHE_CISW,
+    TH_DCACHE_CVAL1,
+    TH_DCACHE_CVA,
+    TH_DCACHE_IVA,
+    TH_DCACHE_CIVA,
+    TH_DCACHE_CPAL1,
+    TH_DCACHE_CPA,
+    TH_DCACHE_IPA,
+    TH_DCACHE_CIPA,
+    TH_ICACHE_IALL,
+    TH_ICACHE_IALLS,
+    TH_ICACHE_IVA,
+    TH_ICACHE_IPA,
+    TH_L2CACHE_CALL,
+    TH_L2CACHE_IALL,
+    TH_L2CACHE_CIALL,
+
+    // CORE-V Vendor Extensions
+    CV_STARTI,
+    CV_START,
+    CV_ENDI,
+    CV_END,
+    CV_COUNTI,
+    CV_COUNT,
+    CV_SETUPI,
+    CV_SETUP,
+    CV_LB,
+    CV_LBU,
+    CV_LH,
+    CV_LHU,
+    CV_LW,
+    CV_SB,
+    CV_SH,
+    CV_SW,
+    CV_MAC,
+    CV_MSU,
+    CV_ABS,
+    CV_SLET,
+    CV_SLETU,
+    CV_MIN,
+    CV_MINU,
+    CV_MAX,
+    CV_MAXU,
+    CV_EXTHS,
+    CV_EXTHZ,
+    CV_EXTBS,
+    CV_EXTBZ,
+    CV_CLIP,
+    CV_CLIPU,
+    CV_CLIPR,
+    CV_CLIPUR,
+    CV_ADD_H,
+    CV_ADD_B,
+    CV_ADD_SC_H,
+    CV_ADD_SC_B,
+    CV_SUB_H,
+    CV_SUB_B,
+    CV_SUB_SC_H,
+    CV_SUB_SC_B,
+    CV_AVG_H,
+    CV_AVG_B,
+    CV_AVG_SC_H,
+    CV_AVG_SC_B,
+    CV_AVGU_H,
+    CV_AVGU_B,
+    CV_AVGU_SC_H,
+    CV_AVGU_SC_B,
+    CV_MIN_H,
+    CV_MIN_B,
+    CV_MIN_SC_H,
+    CV_MIN_SC_B,
+    CV_MINU_H,
+    CV_MINU_B,
+    CV_MINU_SC_H,
+    CV_MINU_SC_B,
+    CV_MAX_H,
+    CV_MAX_B,
+    CV_MAX_SC_H,
+    CV_MAX_SC_B,
+    CV_MAXU_H,
+    CV_MAXU_B,
+    CV_MAXU_SC_H,
+    CV_MAXU_SC_B,
+    CV_SRL_H,
+    CV_SRL_B,
+    CV_SRL_SC_H,
+    CV_SRL_SC_B,
+    CV_SRA_H,
+    CV_SRA_B,
+    CV_SRA_SC_H,
+    CV_SRA_SC_B,
+    CV_SLL_H,
+    CV_SLL_B,
+    CV_SLL_SC_H,
+    CV_SLL_SC_B,
+    CV_OR_H,
+    CV_OR_B,
+    CV_OR_SC_H,
+    CV_OR_SC_B,
+    CV_XOR_H,
+    CV_XOR_B,
+    CV_XOR_SC_H,
+    CV_XOR_SC_B,
+    CV_AND_H,
+    CV_AND_B,
+    CV_AND_SC_H,
+    CV_AND_SC_B,
+    CV_ABS_H,
+    CV_ABS_B,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -188,11 +740,33 @@ pub enum FieldSpec {
     Imm20U,
     /// J-type 20-bit immediate
     Imm20J,
+    /// Floating-point rounding mode (occupies funct3 slot)
+    Rm,
+    /// Vector mask enable bit
+    Vm,
+    /// 2-bit unsigned immediate in bits 26:25
+    Imm2,
+    /// 5-bit unsigned immediate (occupies rs2 slot)
+    Uimm5,
+    /// 6-bit unsigned immediate in bits 25:20
+    Uimm6,
+    /// 5-bit signed immediate (occupies rs2 slot)
+    Simm5,
+    /// 6-bit unsigned immediate in bits 31:26
+    Msb6,
+    /// 12-bit unsigned immediate in bits 31:20
+    Uimm12,
+    /// CSR number in bits 31:20
+    Csr,
+    /// Fence predecessor set in bits 27:24
+    Pred,
+    /// Fence successor set in bits 23:20
+    Succ,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum OperandSpec {
-    Nothing = 0,
+    Nothing,
     Rs1,
     Rs2,
     Rd,
@@ -212,19 +786,249 @@ pub enum OperandSpec {
     Imm20U,
     /// J-type 20-bit immediate
     Imm20J,
+    /// Floating-point register in the rs1 slot
+    FRs1,
+    /// Floating-point register in the rs2 slot
+    FRs2,
+    /// Floating-point register in the rd slot
+    FRd,
+    /// Vector register in the vs1 slot
+    VRs1,
+    /// Vector register in the vs2 slot
+    VRs2,
+    /// Vector register in the vd slot
+    VRd,
+    /// Floating-point rounding mode, omitted when dynamic
+    Rm,
+    /// Vector mask, omitted when unmasked
+    Vm,
+    /// Base register Rs1 without an offset
+    BaseRs1,
+    /// 2-bit unsigned immediate in bits 26:25
+    Imm2,
+    /// 5-bit unsigned immediate (occupies rs2 slot)
+    Uimm5,
+    /// 6-bit unsigned immediate in bits 25:20
+    Uimm6,
+    /// 5-bit signed immediate (occupies rs2 slot)
+    Simm5,
+    /// 6-bit unsigned immediate in bits 31:26
+    Msb6,
+    /// Implied access size shift of a T-Head paired load or store
+    PairShift,
+    /// 12-bit unsigned immediate in bits 31:20
+    Uimm12,
+    /// Rs1 post-incremented by Imm12I
+    PostIncRs1I,
+    /// Rs1 post-incremented by Imm12S
+    PostIncRs1S,
+    /// Rs1 post-incremented by Rs2
+    PostIncRs1Rs2,
+    /// Rs1 post-incremented by the register in the rd slot
+    PostIncRs1Rd,
+    /// Rs1 indexed by Rs2
+    BaseIndexRs1Rs2,
+    /// Rs1 indexed by the register in the rd slot
+    BaseIndexRs1Rd,
+    /// Hardware loop index (bit 7)
+    LoopL,
+    /// Hardware loop target, as a halfword offset in bits 31:20
+    LoopOffset12,
+    /// Hardware loop target, as a halfword offset in the rs1 slot
+    LoopOffset5,
+    /// CSR number
+    Csr,
+    /// 5-bit unsigned immediate (occupies rs1 slot)
+    Zimm,
+    /// Fence predecessor set
+    Pred,
+    /// Fence successor set
+    Succ,
+    /// Operand provided by a [`CustomDecoder`]
+    Custom(u8),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Operand {
     /// GPR operand
     Reg(u8),
+    /// FPR operand
+    FReg(u8),
+    /// Vector register operand
+    VReg(u8),
+    /// Static rounding mode
+    RoundingMode(RoundingMode),
+    /// Vector mask (`v0.t`)
+    VMask,
     /// Immediate
     Imm(i32),
     /// Base(offset)
     BaseOffset(u8, i16),
+    /// (Base): base register without an offset
+    Base(u8),
+    /// (Base), increment: base register updated by an immediate after the access
+    PostIncrement(u8, i16),
+    /// (Base), increment: base register updated by a register after the access
+    PostIncrementReg(u8, u8),
+    /// Index(base): base register indexed by a register
+    BaseIndex(u8, u8),
     Shift(u8),
     LongImm(u32),
     JOffset(i32),
+    /// Control and status register
+    Csr(u16),
+    /// Accesses ordered by a fence: device input (bit 3), device output, memory reads and
+    /// memory writes (bit 0)
+    FenceSet(u8),
+}
+
+/// The kinds of HINT set aside by the base ISA.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HintClass {
+    /// Reserved for future standard HINTs
+    Standard,
+    /// Designated for custom HINTs
+    Custom,
+    /// Zihintntl non-temporal locality hint
+    NonTemporal,
+    /// Zihintpause pause
+    Pause,
+    /// Zicbop cache-block prefetch
+    Prefetch,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RoundingMode {
+    /// Round to nearest, ties to even
+    RNE,
+    /// Round towards zero
+    RTZ,
+    /// Round down
+    RDN,
+    /// Round up
+    RUP,
+    /// Round to nearest, ties to max magnitude
+    RMM,
+}
+
+/// Is `parcel` one of the HINT encodings of the compressed instruction set?
+fn is_compressed_hint(parcel: u16) -> bool {
+    let op = parcel & 0b11;
+    let funct3 = parcel >> 13;
+    let rd = (parcel >> 7) & 0b1_1111;
+    let rs2 = (parcel >> 2) & 0b1_1111;
+    let imm = ((parcel >> 7) & 0b10_0000) | rs2;
+
+    match (op, funct3) {
+        // c.nop with a nonzero immediate, and c.addi with a zero one
+        (0b01, 0b000) => (rd == 0) != (imm == 0),
+        // c.li to zero
+        (0b01, 0b010) => rd == 0,
+        // c.lui to zero
+        (0b01, 0b011) => rd == 0 && imm != 0,
+        // c.srli and c.srai by zero
+        (0b01, 0b100) => (parcel >> 10) & 0b10 == 0 && imm == 0,
+        // c.slli to zero or by zero
+        (0b10, 0b000) => rd == 0 || imm == 0,
+        // c.mv to zero
+        (0b10, 0b100) if parcel & 0x1000 == 0 => rd == 0 && rs2 != 0,
+        // c.add to zero, other than the c.ntl.* hints
+        (0b10, 0b100) => rd == 0 && rs2 != 0 && !(2..=5).contains(&rs2),
+        _ => false,
+    }
+}
+
+/// Length in bytes of the instruction whose first 16-bit parcel is `parcel`, or `None` if the
+/// length is in the range reserved for instructions of 192 bits or more.
+///
+/// Only the low bits of the first parcel determine an instruction's length, so this is suitable
+/// for quickly stepping over instructions without decoding them.
+pub fn instruction_length(parcel: u16) -> Option<u8> {
+    if parcel & 0b11 != 0b11 {
+        Some(2)
+    } else if parcel & 0b1_1100 != 0b1_1100 {
+        Some(4)
+    } else if parcel & 0b11_1111 == 0b01_1111 {
+        Some(6)
+    } else if parcel & 0b111_1111 == 0b011_1111 {
+        Some(8)
+    } else if parcel & 0b111_0000_0000_0000 != 0b111_0000_0000_0000 {
+        // 0b1111111 with nnn in bits 14:12
+        Some(10 + 2 * ((parcel >> 12) & 0b111) as u8)
+    } else {
+        None
+    }
+}
+
+/// Why an instruction could not be decoded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DecodeError {
+    /// The input ended partway through an instruction.
+    ExhaustedInput,
+    /// The encoding is not assigned to any instruction.
+    InvalidOpcode,
+    /// The encoding names an instruction, but with operands it does not allow.
+    InvalidOperand,
+    /// The encoding is reserved by the ISA for future use.
+    Reserved,
+    /// The encoding is a compressed HINT, which this crate does not decode.
+    Hint,
+    /// The instruction belongs to an extension that is not enabled on the [`RiscVDecoder`].
+    ExtensionNotEnabled(Extension),
+    /// The encoding belongs to an extension that this crate does not decode yet.
+    Unimplemented,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::ExtensionNotEnabled(ext) => {
+                write!(f, "instruction from disabled extension {}", ext)
+            }
+            _ => f.write_str(yaxpeax_arch::DecodeError::description(self)),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<ReadError> for DecodeError {
+    fn from(_: ReadError) -> Self {
+        DecodeError::ExhaustedInput
+    }
+}
+
+impl yaxpeax_arch::DecodeError for DecodeError {
+    fn data_exhausted(&self) -> bool {
+        *self == DecodeError::ExhaustedInput
+    }
+
+    fn bad_opcode(&self) -> bool {
+        matches!(
+            self,
+            DecodeError::InvalidOpcode
+                | DecodeError::Reserved
+                | DecodeError::Hint
+                | DecodeError::ExtensionNotEnabled(_)
+                | DecodeError::Unimplemented
+        )
+    }
+
+    fn bad_operand(&self) -> bool {
+        *self == DecodeError::InvalidOperand
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            DecodeError::ExhaustedInput => "exhausted input",
+            DecodeError::InvalidOpcode => "invalid opcode",
+            DecodeError::InvalidOperand => "invalid operand",
+            DecodeError::Reserved => "reserved encoding",
+            DecodeError::Hint => "hint encoding",
+            DecodeError::ExtensionNotEnabled(_) => "instruction from disabled extension",
+            DecodeError::Unimplemented => "unimplemented instruction",
+        }
+    }
 }
 
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
@@ -235,15 +1039,162 @@ impl Arch for RISCV {
     type Address = u32;
     type Word = yaxpeax_arch::U16le;
     type Instruction = Instruction;
-    type DecodeError = StandardDecodeError;
+    type DecodeError = DecodeError;
     type Decoder = RiscVDecoder;
     type Operand = Operand;
 }
 
-#[derive(Default, Debug)]
-pub struct RiscVDecoder {}
+pub struct RiscVDecoder {
+    xlen: Xlen,
+    /// Extensions whose instructions are decoded.
+    extensions: Extensions,
+    /// Decoder consulted first for the custom major opcodes.
+    custom: Option<Box<dyn CustomDecoder + Send + Sync>>,
+}
+
+/// An RV64 decoder for every standard extension, and no vendor extensions.
+impl Default for RiscVDecoder {
+    fn default() -> Self {
+        RiscVDecoder {
+            xlen: Xlen::default(),
+            extensions: Extensions::standard(),
+            custom: None,
+        }
+    }
+}
+
+impl fmt::Debug for RiscVDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RiscVDecoder")
+            .field("xlen", &self.xlen)
+            .field("extensions", &self.extensions)
+            .field("custom", &self.custom.is_some())
+            .finish()
+    }
+}
+
+impl RiscVDecoder {
+    /// Enable decoding of `extension`.
+    pub fn with_extension(mut self, extension: Extension) -> Self {
+        self.extensions.insert(extension);
+        self
+    }
+
+    /// Disable decoding of `extension`.
+    pub fn without_extension(mut self, extension: Extension) -> Self {
+        self.extensions.remove(extension);
+        self
+    }
+
+    /// Enable decoding of every extension in `extensions`.
+    pub fn with_extensions(self, extensions: &[Extension]) -> Self {
+        extensions.iter().fold(self, |decoder, extension| {
+            decoder.with_extension(*extension)
+        })
+    }
+
+    /// Decode for a machine with `xlen`-bit integer registers.
+    pub fn with_xlen(mut self, xlen: Xlen) -> Self {
+        self.xlen = xlen;
+        self
+    }
+
+    /// Decode the custom major opcodes with `decoder` before trying any enabled vendor extensions.
+    pub fn with_custom_decoder<D: CustomDecoder + Send + Sync + 'static>(
+        mut self,
+        decoder: D,
+    ) -> Self {
+        self.custom = Some(Box::new(decoder));
+        self
+    }
+
+    /// Length in bytes of the instruction whose first 16-bit parcel is `parcel`.
+    ///
+    /// See [`instruction_length`].
+    pub fn instruction_length(&self, parcel: u16) -> Option<u8> {
+        instruction_length(parcel)
+    }
+
+    /// Is decoding of `extension` enabled?
+    pub fn has_extension(&self, extension: Extension) -> bool {
+        self.extensions.contains(extension)
+    }
+
+    /// The extensions whose instructions are decoded.
+    pub fn extensions(&self) -> Extensions {
+        self.extensions
+    }
+
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
+}
 
 impl RiscVDecoder {
+    fn decode_parcels<T: Reader<<RISCV as Arch>::Address, <RISCV as Arch>::Word>>(
+        &self,
+        instruction: &mut Instruction,
+        word0: u16,
+        words: &mut T,
+    ) -> Result<(), DecodeError> {
+        // Determine the instruction length first.
+        // Also note: RISC-V instructions are _always_ little-endian.
+        match instruction_length(word0) {
+            Some(2) => {
+                // 16-bit instruction set space.
+                // Unimplemented.
+                if !self.has_extension(Extension::Zca) {
+                    Err(DecodeError::ExtensionNotEnabled(Extension::Zca))
+                } else if word0 == 0 {
+                    // defined to be illegal
+                    Err(DecodeError::InvalidOpcode)
+                } else if is_compressed_hint(word0) {
+                    Err(DecodeError::Hint)
+                } else {
+                    Err(DecodeError::Unimplemented)
+                }
+            }
+            Some(4) => {
+                // 32-bit instruction set space.
+                let word1 = words.next()?.0;
+                let word = ((word1 as u32) << 16u32) | word0 as u32;
+
+                instruction.word = word;
+                instruction.length = 4;
+                self.decode32_into(instruction, word)?;
+
+                match instruction
+                    .opcode
+                    .extensions()
+                    .iter()
+                    .find(|ext| !self.has_extension(*ext))
+                {
+                    Some(ext) => Err(DecodeError::ExtensionNotEnabled(ext)),
+                    None => Ok(()),
+                }
+            }
+            Some(length) => {
+                // 48-bit, 64-bit and (80 + 16 * nnn)-bit instruction set spaces.
+                let word1 = words.next()?.0;
+                for parcel in instruction.parcels_hi[..length as usize / 2 - 2].iter_mut() {
+                    *parcel = words.next()?.0;
+                }
+
+                instruction.word = ((word1 as u32) << 16) | word0 as u32;
+                instruction.length = length;
+                instruction.opcode = Opcode::Opaque;
+                instruction.operands = [OperandSpec::Nothing; 5];
+                Ok(())
+            }
+            None => {
+                // Reserved for instructions of 192 bits or more.
+                Err(DecodeError::Reserved)
+            }
+        }?;
+
+        Ok(())
+    }
+
     fn decode32_into(
         &self,
         instruction: &mut Instruction,
@@ -254,25 +1205,60 @@ impl RiscVDecoder {
         match opc {
             0b011_0111 => {
                 instruction.opcode = Opcode::LUI;
-                instruction.operands = [OperandSpec::Rd, OperandSpec::Imm20U, OperandSpec::Nothing];
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::Imm20U,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
             }
             0b001_0111 => {
                 instruction.opcode = Opcode::AUIPC;
-                instruction.operands = [OperandSpec::Rd, OperandSpec::Imm20U, OperandSpec::Nothing];
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::Imm20U,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
             }
             0b110_1111 => {
                 instruction.opcode = Opcode::JAL;
-                instruction.operands = [OperandSpec::Rd, OperandSpec::Imm20J, OperandSpec::Nothing];
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::Imm20J,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
             }
             0b110_0111 => {
+                // only funct3 0 is allocated in the JALR major opcode
+                if (word >> 12) & 0b111 != 0 {
+                    Err(DecodeError::InvalidOpcode)?
+                }
+
                 instruction.opcode = Opcode::JALR;
-                instruction.operands = [OperandSpec::Rd, OperandSpec::Rs1, OperandSpec::Imm12I];
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::BaseOffsetRs1I,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
             }
             0b110_0011 => {
                 // Bxx opcode group
                 let funct3 = (word >> 12) & 0b111;
 
-                instruction.operands = [OperandSpec::Rs1, OperandSpec::Rs2, OperandSpec::Imm12B];
+                instruction.operands = [
+                    OperandSpec::Rs1,
+                    OperandSpec::Rs2,
+                    OperandSpec::Imm12B,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
                 match funct3 {
                     0b000 => instruction.opcode = Opcode::BEQ,
                     0b001 => instruction.opcode = Opcode::BNE,
@@ -280,7 +1266,7 @@ impl RiscVDecoder {
                     0b101 => instruction.opcode = Opcode::BGE,
                     0b110 => instruction.opcode = Opcode::BLTU,
                     0b111 => instruction.opcode = Opcode::BGEU,
-                    _ => Err(StandardDecodeError::InvalidOpcode)?,
+                    _ => Err(DecodeError::InvalidOpcode)?,
                 }
             }
             0b000_0011 => {
@@ -291,6 +1277,8 @@ impl RiscVDecoder {
                     OperandSpec::Rd,
                     OperandSpec::BaseOffsetRs1I,
                     OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
                 ];
                 match funct3 {
                     0b000 => instruction.opcode = Opcode::LB,
@@ -298,7 +1286,9 @@ impl RiscVDecoder {
                     0b010 => instruction.opcode = Opcode::LW,
                     0b100 => instruction.opcode = Opcode::LBU,
                     0b101 => instruction.opcode = Opcode::LHU,
-                    _ => Err(StandardDecodeError::InvalidOpcode)?,
+                    0b011 if self.xlen == Xlen::X64 => instruction.opcode = Opcode::LD,
+                    0b110 if self.xlen == Xlen::X64 => instruction.opcode = Opcode::LWU,
+                    _ => Err(DecodeError::InvalidOpcode)?,
                 }
             }
             0b010_0011 => {
@@ -309,20 +1299,29 @@ impl RiscVDecoder {
                     OperandSpec::Rs2,
                     OperandSpec::BaseOffsetRs1S,
                     OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
                 ];
                 match funct3 {
                     0b000 => instruction.opcode = Opcode::SB,
                     0b001 => instruction.opcode = Opcode::SH,
                     0b010 => instruction.opcode = Opcode::SW,
-                    _ => Err(StandardDecodeError::InvalidOpcode)?,
+                    0b011 if self.xlen == Xlen::X64 => instruction.opcode = Opcode::SD,
+                    _ => Err(DecodeError::InvalidOpcode)?,
                 }
             }
             0b001_0011 => {
                 // ALU immediate opcode group
                 let funct3 = (word >> 12) & 0b111;
-                let funct7 = (word >> 25) & 0b111_1111;
+                let funct6 = (word >> 26) & 0b11_1111;
 
-                instruction.operands = [OperandSpec::Rd, OperandSpec::Rs1, OperandSpec::Imm12I];
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::Rs1,
+                    OperandSpec::Imm12I,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
                 match funct3 {
                     0b000 => instruction.opcode = Opcode::ADDI,
                     0b010 => instruction.opcode = Opcode::SLTI,
@@ -331,20 +1330,31 @@ impl RiscVDecoder {
                     0b110 => instruction.opcode = Opcode::ORI,
                     0b111 => instruction.opcode = Opcode::ANDI,
                     0b001 | 0b101 => {
-                        instruction.operands =
-                            [OperandSpec::Rd, OperandSpec::Rs1, OperandSpec::Shamt];
-
-                        match funct3 {
-                            0b001 => instruction.opcode = Opcode::SLLI,
-                            0b101 => match funct7 {
-                                0b000_0000 => instruction.opcode = Opcode::SRLI,
-                                0b010_0000 => instruction.opcode = Opcode::SRAI,
-                                _ => Err(StandardDecodeError::InvalidOpcode)?,
-                            },
-                            _ => Err(StandardDecodeError::InvalidOpcode)?,
+                        instruction.operands = [
+                            OperandSpec::Rd,
+                            OperandSpec::Rs1,
+                            OperandSpec::Shamt,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                        ];
+
+                        match (funct3, funct6) {
+                            (0b001, 0b00_0000) => instruction.opcode = Opcode::SLLI,
+                            (0b101, 0b00_0000) => instruction.opcode = Opcode::SRLI,
+                            (0b101, 0b01_0000) => instruction.opcode = Opcode::SRAI,
+                            // Zbb and Zbs shifts, rotates and bit operations
+                            (_, 0b01_0010 | 0b01_1000 | 0b00_1010 | 0b01_1010) => {
+                                Err(DecodeError::Unimplemented)?
+                            }
+                            _ => Err(DecodeError::InvalidOpcode)?,
                         };
+
+                        if self.xlen == Xlen::X32 && instruction.field(FieldSpec::Shamt) >= 32 {
+                            // shamt[5] is reserved in RV32
+                            Err(DecodeError::Reserved)?
+                        }
                     }
-                    _ => Err(StandardDecodeError::InvalidOpcode)?,
+                    _ => Err(DecodeError::InvalidOpcode)?,
                 }
             }
             0b011_0011 => {
@@ -352,37 +1362,299 @@ impl RiscVDecoder {
                 let funct3 = (word >> 12) & 0b111;
                 let funct7 = (word >> 25) & 0b111_1111;
 
-                instruction.operands = [OperandSpec::Rd, OperandSpec::Rs1, OperandSpec::Rs2];
-                match funct3 {
-                    0b000 => match funct7 {
-                        0b000_0000 => instruction.opcode = Opcode::ADD,
-                        0b010_0000 => instruction.opcode = Opcode::SUB,
-                        _ => Err(StandardDecodeError::InvalidOpcode)?,
-                    },
-                    0b001 => instruction.opcode = Opcode::SLL,
-                    0b010 => instruction.opcode = Opcode::SLT,
-                    0b011 => instruction.opcode = Opcode::SLTU,
-                    0b100 => instruction.opcode = Opcode::XOR,
-                    0b101 => match funct7 {
-                        0b000_0000 => instruction.opcode = Opcode::SRL,
-                        0b010_0000 => instruction.opcode = Opcode::SRA,
-                        _ => Err(StandardDecodeError::InvalidOpcode)?,
-                    },
-                    0b110 => instruction.opcode = Opcode::OR,
-                    0b111 => instruction.opcode = Opcode::AND,
-                    _ => Err(StandardDecodeError::InvalidOpcode)?,
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::Rs1,
+                    OperandSpec::Rs2,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
+                match (funct7, funct3) {
+                    (0b000_0000, 0b000) => instruction.opcode = Opcode::ADD,
+                    (0b010_0000, 0b000) => instruction.opcode = Opcode::SUB,
+                    (0b000_0000, 0b001) => instruction.opcode = Opcode::SLL,
+                    (0b000_0000, 0b010) => instruction.opcode = Opcode::SLT,
+                    (0b000_0000, 0b011) => instruction.opcode = Opcode::SLTU,
+                    (0b000_0000, 0b100) => instruction.opcode = Opcode::XOR,
+                    (0b000_0000, 0b101) => instruction.opcode = Opcode::SRL,
+                    (0b010_0000, 0b101) => instruction.opcode = Opcode::SRA,
+                    (0b000_0000, 0b110) => instruction.opcode = Opcode::OR,
+                    (0b000_0000, 0b111) => instruction.opcode = Opcode::AND,
+                    // M, and the Zba, Zbb, Zbc, Zbs and Zicond register-register operations
+                    (0b000_0001 | 0b000_0100 | 0b000_0101 | 0b000_0111, _)
+                    | (0b001_0000 | 0b001_0100 | 0b010_0100 | 0b011_0100 | 0b011_0000, _)
+                    | (0b010_0000, 0b100 | 0b110 | 0b111) => Err(DecodeError::Unimplemented)?,
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                }
+            }
+            0b101_0011 => {
+                // OP-FP opcode group
+                let funct3 = (word >> 12) & 0b111;
+                let funct7 = (word >> 25) & 0b111_1111;
+                let rs2 = (word >> 20) & 0b11111;
+
+                instruction.operands = [
+                    OperandSpec::FRd,
+                    OperandSpec::FRs1,
+                    OperandSpec::Rm,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
+                match (funct7, rs2) {
+                    (0b001_0000 | 0b001_0001, _) => {
+                        // sign injection, selected by funct3 rather than a rounding mode
+                        instruction.operands = [
+                            OperandSpec::FRd,
+                            OperandSpec::FRs1,
+                            OperandSpec::FRs2,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                        ];
+                        match (funct7, funct3) {
+                            (0b001_0000, 0b000) => instruction.opcode = Opcode::FSGNJ_S,
+                            (0b001_0000, 0b001) => instruction.opcode = Opcode::FSGNJN_S,
+                            (0b001_0000, 0b010) => instruction.opcode = Opcode::FSGNJX_S,
+                            (0b001_0001, 0b000) => instruction.opcode = Opcode::FSGNJ_D,
+                            (0b001_0001, 0b001) => instruction.opcode = Opcode::FSGNJN_D,
+                            (0b001_0001, 0b010) => instruction.opcode = Opcode::FSGNJX_D,
+                            _ => Err(DecodeError::InvalidOpcode)?,
+                        }
+                        return Ok(());
+                    }
+                    (0b010_0010, 0b01000) => instruction.opcode = Opcode::FCVT_BF16_S,
+                    (0b010_0000, 0b00110) => instruction.opcode = Opcode::FCVT_S_BF16,
+                    // the rest of F, D, Q, Zfh and Zfa
+                    _ => Err(DecodeError::Unimplemented)?,
+                }
+
+                if instruction.field(FieldSpec::Rm) == 0b101
+                    || instruction.field(FieldSpec::Rm) == 0b110
+                {
+                    // reserved rounding modes
+                    Err(DecodeError::Reserved)?
                 }
             }
+            0b101_0111 => {
+                // OP-V opcode group
+                let funct3 = (word >> 12) & 0b111;
+                let funct6 = (word >> 26) & 0b11_1111;
+                let vs1 = (word >> 15) & 0b11111;
+
+                match (funct3, funct6) {
+                    (0b001, 0b01_0010) => {
+                        // VFUNARY0
+                        instruction.operands = [
+                            OperandSpec::VRd,
+                            OperandSpec::VRs2,
+                            OperandSpec::Vm,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                        ];
+                        match vs1 {
+                            0b11101 => instruction.opcode = Opcode::VFNCVTBF16_F_F_W,
+                            0b01101 => instruction.opcode = Opcode::VFWCVTBF16_F_F_V,
+                            _ => Err(DecodeError::Unimplemented)?,
+                        }
+                    }
+                    (0b001, 0b11_1011) => {
+                        instruction.opcode = Opcode::VFWMACCBF16_VV;
+                        instruction.operands = [
+                            OperandSpec::VRd,
+                            OperandSpec::VRs1,
+                            OperandSpec::VRs2,
+                            OperandSpec::Vm,
+                            OperandSpec::Nothing,
+                        ];
+                    }
+                    (0b101, 0b11_1011) => {
+                        instruction.opcode = Opcode::VFWMACCBF16_VF;
+                        instruction.operands = [
+                            OperandSpec::VRd,
+                            OperandSpec::FRs1,
+                            OperandSpec::VRs2,
+                            OperandSpec::Vm,
+                            OperandSpec::Nothing,
+                        ];
+                    }
+                    // the rest of V
+                    _ => Err(DecodeError::Unimplemented)?,
+                }
+            }
+            0b111_0111 => {
+                // OP-P opcode group, claimed by the draft P extension
+                self.decode_packed(instruction, word)?
+            }
+            0b000_1011 | 0b010_1011 | 0b101_1011 | 0b111_1011 => {
+                // custom-0 through custom-3 opcode groups
+                self.decode_custom(instruction, word)?
+            }
             0b000_1111 => {
-                // FENCE opcode group
-                Err(StandardDecodeError::InvalidOpcode)?
+                // MISC-MEM opcode group
+                let funct3 = (word >> 12) & 0b111;
+                let fm = word >> 28;
+                let pred = (word >> 24) & 0b1111;
+                let succ = (word >> 20) & 0b1111;
+
+                instruction.operands = [OperandSpec::Nothing; 5];
+                match funct3 {
+                    0b000 if fm == 0b1000 && pred == 0b0011 && succ == 0b0011 => {
+                        instruction.opcode = Opcode::FENCE_TSO
+                    }
+                    // fence w, 0, which cores without Zihintpause run as an ordinary fence
+                    0b000 if word == 0x0100_000f && self.has_extension(Extension::Zihintpause) => {
+                        instruction.opcode = Opcode::PAUSE
+                    }
+                    0b000 => {
+                        instruction.opcode = Opcode::FENCE;
+                        instruction.operands = [
+                            OperandSpec::Pred,
+                            OperandSpec::Succ,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                        ];
+                    }
+                    0b001 => instruction.opcode = Opcode::FENCE_I,
+                    // Zicbo* cache-block operations
+                    0b010 => Err(DecodeError::Unimplemented)?,
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                }
             }
-            0b111_0011 => match (opc >> 20) & 0b1111_1111_1111 {
-                0b0000_0000_0000 => instruction.opcode = Opcode::ECALL,
-                0b0000_0000_0001 => instruction.opcode = Opcode::EBREAK,
-                _ => Err(StandardDecodeError::InvalidOpcode)?,
-            },
-            _ => Err(StandardDecodeError::InvalidOpcode)?,
+            0b110_1011 => {
+                // reserved major opcode
+                Err(DecodeError::Reserved)?
+            }
+            0b000_0111 | 0b010_0111 | 0b010_1111 | 0b100_0011 | 0b100_0111 | 0b100_1011
+            | 0b100_1111 => {
+                // LOAD-FP, STORE-FP, AMO, and fused multiply-add opcode groups
+                Err(DecodeError::Unimplemented)?
+            }
+            0b001_1011 if self.xlen == Xlen::X64 => {
+                // ALU immediate opcode group, 32-bit operations
+                let funct3 = (word >> 12) & 0b111;
+                let funct7 = (word >> 25) & 0b111_1111;
+
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::Rs1,
+                    OperandSpec::Shamt,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
+                match (funct3, funct7) {
+                    (0b000, _) => {
+                        instruction.opcode = Opcode::ADDIW;
+                        instruction.operands[2] = OperandSpec::Imm12I;
+                    }
+                    (0b001, 0b000_0000) => instruction.opcode = Opcode::SLLIW,
+                    (0b101, 0b000_0000) => instruction.opcode = Opcode::SRLIW,
+                    (0b101, 0b010_0000) => instruction.opcode = Opcode::SRAIW,
+                    // Zba slli.uw and the Zbb word operations
+                    (0b001, 0b000_0100 | 0b000_0101 | 0b011_0000) | (0b101, 0b011_0000) => {
+                        Err(DecodeError::Unimplemented)?
+                    }
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                }
+            }
+            0b011_1011 if self.xlen == Xlen::X64 => {
+                // ALU opcode group, 32-bit operations
+                let funct3 = (word >> 12) & 0b111;
+                let funct7 = (word >> 25) & 0b111_1111;
+
+                instruction.operands = [
+                    OperandSpec::Rd,
+                    OperandSpec::Rs1,
+                    OperandSpec::Rs2,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
+                match (funct7, funct3) {
+                    (0b000_0000, 0b000) => instruction.opcode = Opcode::ADDW,
+                    (0b010_0000, 0b000) => instruction.opcode = Opcode::SUBW,
+                    (0b000_0000, 0b001) => instruction.opcode = Opcode::SLLW,
+                    (0b000_0000, 0b101) => instruction.opcode = Opcode::SRLW,
+                    (0b010_0000, 0b101) => instruction.opcode = Opcode::SRAW,
+                    // M, and the Zba and Zbb word operations
+                    (0b000_0001 | 0b000_0100 | 0b011_0000, _) | (0b001_0000, _) => {
+                        Err(DecodeError::Unimplemented)?
+                    }
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                }
+            }
+            0b111_0011 => {
+                // SYSTEM opcode group
+                let funct3 = (word >> 12) & 0b111;
+                let funct7 = (word >> 25) & 0b111_1111;
+                let rs1 = (word >> 15) & 0b11111;
+                let rd = (word >> 7) & 0b11111;
+
+                if funct3 != 0b000 {
+                    instruction.operands = [
+                        OperandSpec::Rd,
+                        OperandSpec::Csr,
+                        OperandSpec::Rs1,
+                        OperandSpec::Nothing,
+                        OperandSpec::Nothing,
+                    ];
+                    match funct3 {
+                        0b001 => instruction.opcode = Opcode::CSRRW,
+                        0b010 => instruction.opcode = Opcode::CSRRS,
+                        0b011 => instruction.opcode = Opcode::CSRRC,
+                        0b101 => instruction.opcode = Opcode::CSRRWI,
+                        0b110 => instruction.opcode = Opcode::CSRRSI,
+                        0b111 => instruction.opcode = Opcode::CSRRCI,
+                        // the hypervisor loads and stores
+                        _ => Err(DecodeError::Unimplemented)?,
+                    }
+                    if funct3 & 0b100 != 0 {
+                        instruction.operands[2] = OperandSpec::Zimm;
+                    }
+                    return Ok(());
+                }
+                if rd != 0 {
+                    Err(DecodeError::InvalidOpcode)?
+                }
+
+                instruction.operands = [
+                    OperandSpec::Rs1,
+                    OperandSpec::Rs2,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                    OperandSpec::Nothing,
+                ];
+                match funct7 {
+                    0b000_1011 => instruction.opcode = Opcode::SINVAL_VMA,
+                    0b001_0011 => instruction.opcode = Opcode::HINVAL_VVMA,
+                    0b011_0011 => instruction.opcode = Opcode::HINVAL_GVMA,
+                    // sfence.vma, hfence.vvma and hfence.gvma
+                    0b000_1001 | 0b001_0001 | 0b011_0001 => Err(DecodeError::Unimplemented)?,
+                    _ => {
+                        // the remaining encodings take no register operands
+                        if rs1 != 0 {
+                            Err(DecodeError::InvalidOpcode)?
+                        }
+
+                        instruction.operands = [
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                            OperandSpec::Nothing,
+                        ];
+                        match (word >> 20) & 0b1111_1111_1111 {
+                            0b0000_0000_0000 => instruction.opcode = Opcode::ECALL,
+                            0b0000_0000_0001 => instruction.opcode = Opcode::EBREAK,
+                            0b0001_1000_0000 => instruction.opcode = Opcode::SFENCE_W_INVAL,
+                            0b0001_1000_0001 => instruction.opcode = Opcode::SFENCE_INVAL_IR,
+                            // the privileged architecture's returns, wfi and fences
+                            0b0001_0000_0010 | 0b0011_0000_0010 | 0b0111_0000_0010
+                            | 0b0001_0000_0101 => Err(DecodeError::Unimplemented)?,
+                            _ => Err(DecodeError::InvalidOpcode)?,
+                        }
+                    }
+                }
+            }
+            _ => Err(DecodeError::InvalidOpcode)?,
         }
 
         Ok(())
@@ -397,26 +1669,21 @@ impl Decoder<RISCV> for RiscVDecoder {
     ) -> Result<(), <RISCV as Arch>::DecodeError> {
         let word0 = words.next()?.0;
 
-        // Determine the instruction length first.
-        // Also note: RISC-V instructions are _always_ little-endian.
-        let opc = word0 & 0b111_1111;
-        match opc {
-            opc if opc & 0b11 != 0b11 => {
-                // 16-bit instruction set space.
-                // Unimplemented.
-                Err(StandardDecodeError::InvalidOpcode)
-            }
-            opc if opc & 0b1_1100 != 0b1_1100 => {
-                // 32-bit instruction set space.
-                let word1 = words.next()?.0;
-                let word = ((word1 as u32) << 16u32) | word0 as u32;
-
-                instruction.word = word;
-                self.decode32_into(instruction, word)
-            }
-            _ => Err(StandardDecodeError::InvalidOpcode),
-        }?;
+        // Until the instruction decodes, it is just the parcels read so far.
+        instruction.word = word0 as u32;
+        instruction.parcels_hi = [0; 9];
+        instruction.length = 2;
+        instruction.opcode = Opcode::Invalid;
+        instruction.operands = [OperandSpec::Nothing; 5];
+        instruction.custom_operands = [None; 5];
 
-        Ok(())
+        let result = self.decode_parcels(instruction, word0, words);
+        if result.is_err() {
+            // leave no half-decoded opcode or operands behind
+            instruction.opcode = Opcode::Invalid;
+            instruction.operands = [OperandSpec::Nothing; 5];
+            instruction.custom_operands = [None; 5];
+        }
+        result
     }
 }
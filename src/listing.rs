@@ -0,0 +1,246 @@
+//! Listings in the style of `objdump -d`: each instruction on its own line, after its address
+//! and encoding and separated from them by tabs, as in
+//!
+//! ```text
+//!     1000:   00c58533            add     a0,a1,a2
+//!     1004:   0005                .half   0x5
+//! ```
+//!
+//! Encodings are shown as objdump shows them, as little-endian values: 4 hex digits
+//! for 16-bit instructions, 8 for 32-bit ones, and groups of 32-bit words or 16-bit parcels for
+//! longer ones.
+
+use std::fmt;
+
+use yaxpeax_arch::{Decoder, U8Reader};
+
+use crate::{
+    instruction_length, DecodeError, DisplayContext, DisplayOptions, Instruction, RiscVDecoder,
+    Symbolizer,
+};
+
+/// Formats instructions as lines of an objdump-style listing.
+#[derive(Copy, Clone)]
+pub struct Listing<'a> {
+    context: DisplayContext<'a>,
+    address_width: usize,
+    encoding_width: usize,
+}
+
+impl fmt::Debug for Listing<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Listing")
+            .field("context", &self.context)
+            .field("address_width", &self.address_width)
+            .field("encoding_width", &self.encoding_width)
+            .finish()
+    }
+}
+
+impl Default for Listing<'_> {
+    fn default() -> Self {
+        Listing {
+            context: DisplayContext::default(),
+            address_width: 8,
+            encoding_width: 18,
+        }
+    }
+}
+
+impl<'a> Listing<'a> {
+    /// A listing with the default display options, addresses right-aligned to 8 columns and
+    /// encodings left-aligned to 18, as objdump lays out 32-bit code.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show instructions as `options` describe.
+    pub fn with_options(mut self, options: DisplayOptions) -> Self {
+        self.context = self.context.with_options(options);
+        self
+    }
+
+    /// The options instructions are shown with.
+    pub fn options(&self) -> DisplayOptions {
+        self.context.options()
+    }
+
+    /// Right-align addresses to `width` columns.
+    pub fn with_address_width(mut self, width: usize) -> Self {
+        self.address_width = width;
+        self
+    }
+
+    /// The number of columns addresses are right-aligned to.
+    pub fn address_width(&self) -> usize {
+        self.address_width
+    }
+
+    /// Pad encodings with spaces to `width` columns. Longer encodings are shown whole.
+    pub fn with_encoding_width(mut self, width: usize) -> Self {
+        self.encoding_width = width;
+        self
+    }
+
+    /// The number of columns encodings are padded to.
+    pub fn encoding_width(&self) -> usize {
+        self.encoding_width
+    }
+
+    /// Follow branch and jump targets with the symbol `symbolizer` names for them.
+    pub fn with_symbolizer(mut self, symbolizer: &'a dyn Symbolizer) -> Self {
+        self.context = self.context.with_symbolizer(symbolizer);
+        self
+    }
+
+    /// The line for `instruction` at `address`, without a trailing newline.
+    pub fn line<'i>(&self, instruction: &'i Instruction, address: u64) -> ListingLine<'i>
+    where
+        'a: 'i,
+    {
+        let mut encoding = [0; 22];
+        let mut len = 0;
+        while let Some(parcel) = instruction.parcel(len / 2) {
+            encoding[len..len + 2].copy_from_slice(&parcel.to_le_bytes());
+            len += 2;
+        }
+        ListingLine {
+            listing: *self,
+            instruction,
+            address,
+            encoding,
+            len,
+        }
+    }
+
+    /// Decode `data` with `decoder`, as code at `address`, and write a line per instruction to
+    /// `out`, each ending in a newline.
+    ///
+    /// Instructions that do not decode are shown as data, as objdump shows them, and the listing
+    /// carries on after them. An instruction cut off by the end of `data` is shown as data too, a
+    /// `.half` per parcel and a `.byte` for a trailing odd byte.
+    pub fn write_buffer<W: fmt::Write + ?Sized>(
+        &self,
+        decoder: &RiscVDecoder,
+        data: &[u8],
+        address: u64,
+        out: &mut W,
+    ) -> fmt::Result {
+        let mut previous = None;
+        let mut offset = 0;
+        while data.len() - offset >= 2 {
+            let rest = &data[offset..];
+            let at = address.wrapping_add(offset as u64);
+            let mut instr = Instruction::default();
+            match decoder.decode_into(&mut instr, &mut U8Reader::new(rest)) {
+                Ok(()) => {
+                    let len = instr.length as usize;
+                    self.write_address(out, at)?;
+                    write_encoding(out, &rest[..len], self.encoding_width)?;
+                    let mut display = self.context.display_at(&instr, at);
+                    if let Some(previous) = previous.as_ref() {
+                        display = display.after(previous);
+                    }
+                    writeln!(out, "{}", display)?;
+                    previous = Some(instr);
+                    offset += len;
+                }
+                Err(DecodeError::ExhaustedInput) => break,
+                Err(_) => {
+                    // a 32-bit word is shown as one, anything else by its first parcel
+                    let parcel = u16::from_le_bytes([rest[0], rest[1]]);
+                    let len = match instruction_length(parcel) {
+                        Some(4) => 4,
+                        _ => 2,
+                    };
+                    self.write_data(out, at, &rest[..len])?;
+                    previous = None;
+                    offset += len;
+                }
+            }
+        }
+        for chunk in data[offset..].chunks(2) {
+            self.write_data(out, address.wrapping_add(offset as u64), chunk)?;
+            offset += chunk.len();
+        }
+        Ok(())
+    }
+
+    /// Write `bytes` at `address` as a `.byte`, `.half` or `.word` directive.
+    fn write_data<W: fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        address: u64,
+        bytes: &[u8],
+    ) -> fmt::Result {
+        self.write_address(out, address)?;
+        write_encoding(out, bytes, self.encoding_width)?;
+        let directive = match bytes.len() {
+            1 => ".byte",
+            2 => ".half",
+            _ => ".word",
+        };
+        let value = bytes
+            .iter()
+            .rev()
+            .fold(0u32, |value, byte| (value << 8) | *byte as u32);
+        writeln!(out, "{}\t{:#x}", directive, value)
+    }
+
+    fn write_address<W: fmt::Write + ?Sized>(&self, out: &mut W, address: u64) -> fmt::Result {
+        write!(out, "{:>width$x}:\t", address, width = self.address_width)
+    }
+}
+
+/// One line of a [`Listing`]: an instruction after its address and encoding.
+#[derive(Copy, Clone)]
+pub struct ListingLine<'a> {
+    listing: Listing<'a>,
+    instruction: &'a Instruction,
+    address: u64,
+    encoding: [u8; 22],
+    len: usize,
+}
+
+impl fmt::Debug for ListingLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ListingLine")
+            .field("listing", &self.listing)
+            .field("instruction", &self.instruction)
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl fmt::Display for ListingLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let listing = &self.listing;
+        listing.write_address(f, self.address)?;
+        write_encoding(f, &self.encoding[..self.len], listing.encoding_width)?;
+        write!(
+            f,
+            "{}",
+            listing.context.display_at(self.instruction, self.address)
+        )
+    }
+}
+
+/// Write `bytes` as objdump does, in little-endian 32-bit words if they divide evenly into
+/// them and 16-bit parcels otherwise, padded to `width` columns and followed by a tab.
+// `usize::is_multiple_of` is newer than the rest of the crate needs
+#[allow(clippy::manual_is_multiple_of)]
+fn write_encoding<W: fmt::Write + ?Sized>(out: &mut W, bytes: &[u8], width: usize) -> fmt::Result {
+    let chunk = if bytes.len() % 4 == 0 { 4 } else { 2 };
+    let mut written = 0;
+    for (i, group) in bytes.chunks(chunk).enumerate() {
+        if i != 0 {
+            out.write_char(' ')?;
+            written += 1;
+        }
+        for byte in group.iter().rev() {
+            write!(out, "{:02x}", byte)?;
+            written += 2;
+        }
+    }
+    write!(out, "{:pad$}\t", "", pad = width.saturating_sub(written))
+}
@@ -0,0 +1,105 @@
+//! The draft packed-SIMD (P) extension.
+//!
+//! Its encodings live in the OP-P major opcode, which the ratified extensions leave reserved,
+//! and are only decoded when the crate is built with the `p-ext` feature. Without it, OP-P is
+//! reserved like any other unclaimed major opcode.
+
+use crate::{DecodeError, Instruction, RiscVDecoder};
+#[cfg(feature = "p-ext")]
+use crate::{Opcode, OperandSpec};
+
+impl RiscVDecoder {
+    /// Decode `word`, an instruction in the OP-P major opcode, as a packed-SIMD instruction.
+    #[cfg(feature = "p-ext")]
+    pub(crate) fn decode_packed(
+        &self,
+        instruction: &mut Instruction,
+        word: u32,
+    ) -> Result<(), DecodeError> {
+        let funct3 = (word >> 12) & 0b111;
+        let funct7 = (word >> 25) & 0b111_1111;
+
+        instruction.operands = [
+            OperandSpec::Rd,
+            OperandSpec::Rs1,
+            OperandSpec::Rs2,
+            OperandSpec::Nothing,
+            OperandSpec::Nothing,
+        ];
+        match (funct3, funct7) {
+            (0b000, 0b010_0000) => instruction.opcode = Opcode::ADD16,
+            (0b000, 0b000_0000) => instruction.opcode = Opcode::RADD16,
+            (0b000, 0b001_0000) => instruction.opcode = Opcode::URADD16,
+            (0b000, 0b000_1000) => instruction.opcode = Opcode::KADD16,
+            (0b000, 0b001_1000) => instruction.opcode = Opcode::UKADD16,
+            (0b000, 0b010_0001) => instruction.opcode = Opcode::SUB16,
+            (0b000, 0b000_0001) => instruction.opcode = Opcode::RSUB16,
+            (0b000, 0b001_0001) => instruction.opcode = Opcode::URSUB16,
+            (0b000, 0b000_1001) => instruction.opcode = Opcode::KSUB16,
+            (0b000, 0b001_1001) => instruction.opcode = Opcode::UKSUB16,
+            (0b000, 0b010_0010) => instruction.opcode = Opcode::CRAS16,
+            (0b000, 0b000_0010) => instruction.opcode = Opcode::RCRAS16,
+            (0b000, 0b000_1010) => instruction.opcode = Opcode::KCRAS16,
+            (0b000, 0b010_0011) => instruction.opcode = Opcode::CRSA16,
+            (0b000, 0b000_0011) => instruction.opcode = Opcode::RCRSA16,
+            (0b000, 0b000_1011) => instruction.opcode = Opcode::KCRSA16,
+            (0b000, 0b010_0100) => instruction.opcode = Opcode::ADD8,
+            (0b000, 0b000_0100) => instruction.opcode = Opcode::RADD8,
+            (0b000, 0b001_0100) => instruction.opcode = Opcode::URADD8,
+            (0b000, 0b000_1100) => instruction.opcode = Opcode::KADD8,
+            (0b000, 0b001_1100) => instruction.opcode = Opcode::UKADD8,
+            (0b000, 0b010_0101) => instruction.opcode = Opcode::SUB8,
+            (0b000, 0b000_0101) => instruction.opcode = Opcode::RSUB8,
+            (0b000, 0b001_0101) => instruction.opcode = Opcode::URSUB8,
+            (0b000, 0b000_1101) => instruction.opcode = Opcode::KSUB8,
+            (0b000, 0b001_1101) => instruction.opcode = Opcode::UKSUB8,
+            (0b000, 0b010_1000) => instruction.opcode = Opcode::SRA16,
+            (0b000, 0b010_1001) => instruction.opcode = Opcode::SRL16,
+            (0b000, 0b010_1010) => instruction.opcode = Opcode::SLL16,
+            (0b000, 0b010_1100) => instruction.opcode = Opcode::SRA8,
+            (0b000, 0b010_1101) => instruction.opcode = Opcode::SRL8,
+            (0b000, 0b010_1110) => instruction.opcode = Opcode::SLL8,
+            (0b000, 0b010_0110) => instruction.opcode = Opcode::CMPEQ16,
+            (0b000, 0b000_0110) => instruction.opcode = Opcode::SCMPLT16,
+            (0b000, 0b000_1110) => instruction.opcode = Opcode::SCMPLE16,
+            (0b000, 0b001_0110) => instruction.opcode = Opcode::UCMPLT16,
+            (0b000, 0b001_1110) => instruction.opcode = Opcode::UCMPLE16,
+            (0b000, 0b010_0111) => instruction.opcode = Opcode::CMPEQ8,
+            (0b000, 0b000_0111) => instruction.opcode = Opcode::SCMPLT8,
+            (0b000, 0b000_1111) => instruction.opcode = Opcode::SCMPLE8,
+            (0b000, 0b001_0111) => instruction.opcode = Opcode::UCMPLT8,
+            (0b000, 0b001_1111) => instruction.opcode = Opcode::UCMPLE8,
+            (0b000, 0b100_0000) => instruction.opcode = Opcode::SMIN16,
+            (0b000, 0b100_1000) => instruction.opcode = Opcode::UMIN16,
+            (0b000, 0b100_0001) => instruction.opcode = Opcode::SMAX16,
+            (0b000, 0b100_1001) => instruction.opcode = Opcode::UMAX16,
+            (0b000, 0b100_0100) => instruction.opcode = Opcode::SMIN8,
+            (0b000, 0b100_1100) => instruction.opcode = Opcode::UMIN8,
+            (0b000, 0b100_0101) => instruction.opcode = Opcode::SMAX8,
+            (0b000, 0b100_1101) => instruction.opcode = Opcode::UMAX8,
+            (0b000, 0b100_0011) => instruction.opcode = Opcode::KHM16,
+            (0b000, 0b100_1011) => instruction.opcode = Opcode::KHMX16,
+            (0b000, 0b100_0111) => instruction.opcode = Opcode::KHM8,
+            (0b000, 0b100_1111) => instruction.opcode = Opcode::KHMX8,
+            (0b000, 0b110_0100) => instruction.opcode = Opcode::SMAQA,
+            (0b000, 0b110_0110) => instruction.opcode = Opcode::UMAQA,
+            (0b000, 0b110_0101) => instruction.opcode = Opcode::SMAQA_SU,
+            (0b001, 0b000_0111) => instruction.opcode = Opcode::PKBB16,
+            (0b001, 0b000_1111) => instruction.opcode = Opcode::PKBT16,
+            (0b001, 0b001_0111) => instruction.opcode = Opcode::PKTB16,
+            (0b001, 0b001_1111) => instruction.opcode = Opcode::PKTT16,
+            _ => Err(DecodeError::InvalidOpcode)?,
+        }
+        Ok(())
+    }
+
+    /// OP-P is reserved without the `p-ext` feature.
+    #[cfg(not(feature = "p-ext"))]
+    pub(crate) fn decode_packed(
+        &self,
+        _instruction: &mut Instruction,
+        _word: u32,
+    ) -> Result<(), DecodeError> {
+        Err(DecodeError::Reserved)
+    }
+}
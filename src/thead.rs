@@ -0,0 +1,349 @@
+//! T-Head vendor extensions, as implemented by the XuanTie C906 and C910 cores.
+//!
+//! All of these live in the custom-0 major opcode and are only decoded when the corresponding
+//! [`Extension`] has been enabled on the [`RiscVDecoder`].
+
+use crate::{DecodeError, Extension, Instruction, Opcode, OperandSpec, RiscVDecoder, Xlen};
+
+const NO_OPERANDS: [OperandSpec; 5] = [OperandSpec::Nothing; 5];
+const RS1: [OperandSpec; 5] = [
+    OperandSpec::Rs1,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RS1_RS2: [OperandSpec; 5] = [
+    OperandSpec::Rs1,
+    OperandSpec::Rs2,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_RS1: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_RS1_RS2: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Rs2,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_RS1_RS2_IMM2: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Rs2,
+    OperandSpec::Imm2,
+    OperandSpec::Nothing,
+];
+const FRD_RS1_RS2_IMM2: [OperandSpec; 5] = [
+    OperandSpec::FRd,
+    OperandSpec::Rs1,
+    OperandSpec::Rs2,
+    OperandSpec::Imm2,
+    OperandSpec::Nothing,
+];
+const RD_RS1_SHAMT: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Shamt,
+    OperandSpec::Nothing,
+    OperandSpec::Nothing,
+];
+const RD_RS1_MSB_LSB: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs1,
+    OperandSpec::Msb6,
+    OperandSpec::Uimm6,
+    OperandSpec::Nothing,
+];
+const RD_BASE_SIMM5_IMM2: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::BaseRs1,
+    OperandSpec::Simm5,
+    OperandSpec::Imm2,
+    OperandSpec::Nothing,
+];
+const RD_RS2_BASE_IMM2_SHIFT: [OperandSpec; 5] = [
+    OperandSpec::Rd,
+    OperandSpec::Rs2,
+    OperandSpec::BaseRs1,
+    OperandSpec::Imm2,
+    OperandSpec::PairShift,
+];
+
+impl RiscVDecoder {
+    /// Decode `word`, an instruction in the custom-0 major opcode, as a T-Head vendor instruction.
+    pub(crate) fn decode_thead(
+        &self,
+        instruction: &mut Instruction,
+        word: u32,
+    ) -> Result<(), DecodeError> {
+        let funct3 = (word >> 12) & 0b111;
+        let funct5 = (word >> 27) & 0b1_1111;
+        let funct7 = (word >> 25) & 0b111_1111;
+        let rs2 = (word >> 20) & 0b11111;
+        let rs1 = (word >> 15) & 0b11111;
+        let rd = (word >> 7) & 0b11111;
+
+        let (extension, opcode, operands) = match funct3 {
+            0b000 => {
+                // cache management and synchronization; none of these write a register
+                if rd != 0 {
+                    Err(DecodeError::InvalidOpcode)?
+                }
+
+                match (funct7, rs2) {
+                    (0b000_0000, _) if rs1 != 0 => Err(DecodeError::InvalidOpcode)?,
+                    (0b000_0000, 0b00001) => {
+                        (Extension::XTheadCmo, Opcode::TH_DCACHE_CALL, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b00010) => {
+                        (Extension::XTheadCmo, Opcode::TH_DCACHE_IALL, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b00011) => {
+                        (Extension::XTheadCmo, Opcode::TH_DCACHE_CIALL, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b10000) => {
+                        (Extension::XTheadCmo, Opcode::TH_ICACHE_IALL, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b10001) => {
+                        (Extension::XTheadCmo, Opcode::TH_ICACHE_IALLS, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b10101) => {
+                        (Extension::XTheadCmo, Opcode::TH_L2CACHE_CALL, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b10110) => {
+                        (Extension::XTheadCmo, Opcode::TH_L2CACHE_IALL, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b10111) => {
+                        (Extension::XTheadCmo, Opcode::TH_L2CACHE_CIALL, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b11000) => (Extension::XTheadSync, Opcode::TH_SYNC, NO_OPERANDS),
+                    (0b000_0000, 0b11001) => {
+                        (Extension::XTheadSync, Opcode::TH_SYNC_S, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b11010) => {
+                        (Extension::XTheadSync, Opcode::TH_SYNC_I, NO_OPERANDS)
+                    }
+                    (0b000_0000, 0b11011) => {
+                        (Extension::XTheadSync, Opcode::TH_SYNC_IS, NO_OPERANDS)
+                    }
+                    (0b000_0001, 0b00001) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CSW, RS1),
+                    (0b000_0001, 0b00010) => (Extension::XTheadCmo, Opcode::TH_DCACHE_ISW, RS1),
+                    (0b000_0001, 0b00011) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CISW, RS1),
+                    (0b000_0001, 0b00100) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CVAL1, RS1),
+                    (0b000_0001, 0b00101) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CVA, RS1),
+                    (0b000_0001, 0b00110) => (Extension::XTheadCmo, Opcode::TH_DCACHE_IVA, RS1),
+                    (0b000_0001, 0b00111) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CIVA, RS1),
+                    (0b000_0001, 0b01000) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CPAL1, RS1),
+                    (0b000_0001, 0b01001) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CPA, RS1),
+                    (0b000_0001, 0b01010) => (Extension::XTheadCmo, Opcode::TH_DCACHE_IPA, RS1),
+                    (0b000_0001, 0b01011) => (Extension::XTheadCmo, Opcode::TH_DCACHE_CIPA, RS1),
+                    (0b000_0001, 0b10000) => (Extension::XTheadCmo, Opcode::TH_ICACHE_IVA, RS1),
+                    (0b000_0001, 0b11000) => (Extension::XTheadCmo, Opcode::TH_ICACHE_IPA, RS1),
+                    (0b000_0010, _) => (Extension::XTheadSync, Opcode::TH_SFENCE_VMAS, RS1_RS2),
+                    _ => Err(DecodeError::InvalidOpcode)?,
+                }
+            }
+            0b001 => match funct7 {
+                0b000_0000..=0b000_0011 => (Extension::XTheadBa, Opcode::TH_ADDSL, RD_RS1_RS2_IMM2),
+                0b000_1000 | 0b000_1001 => (Extension::XTheadBb, Opcode::TH_SRRI, RD_RS1_SHAMT),
+                0b000_1010 => (Extension::XTheadBb, Opcode::TH_SRRIW, RD_RS1_SHAMT),
+                0b001_0000 => (Extension::XTheadMac, Opcode::TH_MULA, RD_RS1_RS2),
+                0b001_0001 => (Extension::XTheadMac, Opcode::TH_MULS, RD_RS1_RS2),
+                0b001_0010 => (Extension::XTheadMac, Opcode::TH_MULAW, RD_RS1_RS2),
+                0b001_0011 => (Extension::XTheadMac, Opcode::TH_MULSW, RD_RS1_RS2),
+                0b001_0100 => (Extension::XTheadMac, Opcode::TH_MULAH, RD_RS1_RS2),
+                0b001_0101 => (Extension::XTheadMac, Opcode::TH_MULSH, RD_RS1_RS2),
+                0b010_0000 => (Extension::XTheadCondMov, Opcode::TH_MVEQZ, RD_RS1_RS2),
+                0b010_0001 => (Extension::XTheadCondMov, Opcode::TH_MVNEZ, RD_RS1_RS2),
+                0b100_0100 | 0b100_0101 => (Extension::XTheadBs, Opcode::TH_TST, RD_RS1_SHAMT),
+                _ => {
+                    // single-source bit manipulation, with rs2 reserved as zero
+                    if rs2 != 0 {
+                        Err(DecodeError::InvalidOpcode)?
+                    }
+
+                    match funct7 {
+                        0b100_0000 => (Extension::XTheadBb, Opcode::TH_TSTNBZ, RD_RS1),
+                        0b100_0001 => (Extension::XTheadBb, Opcode::TH_REV, RD_RS1),
+                        0b100_0010 => (Extension::XTheadBb, Opcode::TH_FF0, RD_RS1),
+                        0b100_0011 => (Extension::XTheadBb, Opcode::TH_FF1, RD_RS1),
+                        0b100_1000 => (Extension::XTheadBb, Opcode::TH_REVW, RD_RS1),
+                        _ => Err(DecodeError::InvalidOpcode)?,
+                    }
+                }
+            },
+            0b010 => (Extension::XTheadBb, Opcode::TH_EXT, RD_RS1_MSB_LSB),
+            0b011 => (Extension::XTheadBb, Opcode::TH_EXTU, RD_RS1_MSB_LSB),
+            0b100 => match funct5 {
+                0b00000 => (Extension::XTheadMemIdx, Opcode::TH_LRB, RD_RS1_RS2_IMM2),
+                0b00001 => (Extension::XTheadMemIdx, Opcode::TH_LBIB, RD_BASE_SIMM5_IMM2),
+                0b00010 => (Extension::XTheadMemIdx, Opcode::TH_LURB, RD_RS1_RS2_IMM2),
+                0b00011 => (Extension::XTheadMemIdx, Opcode::TH_LBIA, RD_BASE_SIMM5_IMM2),
+                0b00100 => (Extension::XTheadMemIdx, Opcode::TH_LRH, RD_RS1_RS2_IMM2),
+                0b00101 => (Extension::XTheadMemIdx, Opcode::TH_LHIB, RD_BASE_SIMM5_IMM2),
+                0b00110 => (Extension::XTheadMemIdx, Opcode::TH_LURH, RD_RS1_RS2_IMM2),
+                0b00111 => (Extension::XTheadMemIdx, Opcode::TH_LHIA, RD_BASE_SIMM5_IMM2),
+                0b01000 => (Extension::XTheadMemIdx, Opcode::TH_LRW, RD_RS1_RS2_IMM2),
+                0b01001 => (Extension::XTheadMemIdx, Opcode::TH_LWIB, RD_BASE_SIMM5_IMM2),
+                0b01010 => (Extension::XTheadMemIdx, Opcode::TH_LURW, RD_RS1_RS2_IMM2),
+                0b01011 => (Extension::XTheadMemIdx, Opcode::TH_LWIA, RD_BASE_SIMM5_IMM2),
+                0b01100 => (Extension::XTheadMemIdx, Opcode::TH_LRD, RD_RS1_RS2_IMM2),
+                0b01101 => (Extension::XTheadMemIdx, Opcode::TH_LDIB, RD_BASE_SIMM5_IMM2),
+                0b01110 => (Extension::XTheadMemIdx, Opcode::TH_LURD, RD_RS1_RS2_IMM2),
+                0b01111 => (Extension::XTheadMemIdx, Opcode::TH_LDIA, RD_BASE_SIMM5_IMM2),
+                0b10000 => (Extension::XTheadMemIdx, Opcode::TH_LRBU, RD_RS1_RS2_IMM2),
+                0b10001 => (
+                    Extension::XTheadMemIdx,
+                    Opcode::TH_LBUIB,
+                    RD_BASE_SIMM5_IMM2,
+                ),
+                0b10010 => (Extension::XTheadMemIdx, Opcode::TH_LURBU, RD_RS1_RS2_IMM2),
+                0b10011 => (
+                    Extension::XTheadMemIdx,
+                    Opcode::TH_LBUIA,
+                    RD_BASE_SIMM5_IMM2,
+                ),
+                0b10100 => (Extension::XTheadMemIdx, Opcode::TH_LRHU, RD_RS1_RS2_IMM2),
+                0b10101 => (
+                    Extension::XTheadMemIdx,
+                    Opcode::TH_LHUIB,
+                    RD_BASE_SIMM5_IMM2,
+                ),
+                0b10110 => (Extension::XTheadMemIdx, Opcode::TH_LURHU, RD_RS1_RS2_IMM2),
+                0b10111 => (
+                    Extension::XTheadMemIdx,
+                    Opcode::TH_LHUIA,
+                    RD_BASE_SIMM5_IMM2,
+                ),
+                0b11000 => (Extension::XTheadMemIdx, Opcode::TH_LRWU, RD_RS1_RS2_IMM2),
+                0b11001 => (
+                    Extension::XTheadMemIdx,
+                    Opcode::TH_LWUIB,
+                    RD_BASE_SIMM5_IMM2,
+                ),
+                0b11010 => (Extension::XTheadMemIdx, Opcode::TH_LURWU, RD_RS1_RS2_IMM2),
+                0b11011 => (
+                    Extension::XTheadMemIdx,
+                    Opcode::TH_LWUIA,
+                    RD_BASE_SIMM5_IMM2,
+                ),
+                0b11100 => (
+                    Extension::XTheadMemPair,
+                    Opcode::TH_LWD,
+                    RD_RS2_BASE_IMM2_SHIFT,
+                ),
+                0b11110 => (
+                    Extension::XTheadMemPair,
+                    Opcode::TH_LWUD,
+                    RD_RS2_BASE_IMM2_SHIFT,
+                ),
+                0b11111 => (
+                    Extension::XTheadMemPair,
+                    Opcode::TH_LDD,
+                    RD_RS2_BASE_IMM2_SHIFT,
+                ),
+                _ => Err(DecodeError::InvalidOpcode)?,
+            },
+            0b101 => match funct5 {
+                0b00000 => (Extension::XTheadMemIdx, Opcode::TH_SRB, RD_RS1_RS2_IMM2),
+                0b00001 => (Extension::XTheadMemIdx, Opcode::TH_SBIB, RD_BASE_SIMM5_IMM2),
+                0b00010 => (Extension::XTheadMemIdx, Opcode::TH_SURB, RD_RS1_RS2_IMM2),
+                0b00011 => (Extension::XTheadMemIdx, Opcode::TH_SBIA, RD_BASE_SIMM5_IMM2),
+                0b00100 => (Extension::XTheadMemIdx, Opcode::TH_SRH, RD_RS1_RS2_IMM2),
+                0b00101 => (Extension::XTheadMemIdx, Opcode::TH_SHIB, RD_BASE_SIMM5_IMM2),
+                0b00110 => (Extension::XTheadMemIdx, Opcode::TH_SURH, RD_RS1_RS2_IMM2),
+                0b00111 => (Extension::XTheadMemIdx, Opcode::TH_SHIA, RD_BASE_SIMM5_IMM2),
+                0b01000 => (Extension::XTheadMemIdx, Opcode::TH_SRW, RD_RS1_RS2_IMM2),
+                0b01001 => (Extension::XTheadMemIdx, Opcode::TH_SWIB, RD_BASE_SIMM5_IMM2),
+                0b01010 => (Extension::XTheadMemIdx, Opcode::TH_SURW, RD_RS1_RS2_IMM2),
+                0b01011 => (Extension::XTheadMemIdx, Opcode::TH_SWIA, RD_BASE_SIMM5_IMM2),
+                0b01100 => (Extension::XTheadMemIdx, Opcode::TH_SRD, RD_RS1_RS2_IMM2),
+                0b01101 => (Extension::XTheadMemIdx, Opcode::TH_SDIB, RD_BASE_SIMM5_IMM2),
+                0b01110 => (Extension::XTheadMemIdx, Opcode::TH_SURD, RD_RS1_RS2_IMM2),
+                0b01111 => (Extension::XTheadMemIdx, Opcode::TH_SDIA, RD_BASE_SIMM5_IMM2),
+                0b11100 => (
+                    Extension::XTheadMemPair,
+                    Opcode::TH_SWD,
+                    RD_RS2_BASE_IMM2_SHIFT,
+                ),
+                0b11111 => (
+                    Extension::XTheadMemPair,
+                    Opcode::TH_SDD,
+                    RD_RS2_BASE_IMM2_SHIFT,
+                ),
+                _ => Err(DecodeError::InvalidOpcode)?,
+            },
+            0b110 => match funct5 {
+                0b01000 => (Extension::XTheadFMemIdx, Opcode::TH_FLRW, FRD_RS1_RS2_IMM2),
+                0b01010 => (Extension::XTheadFMemIdx, Opcode::TH_FLURW, FRD_RS1_RS2_IMM2),
+                0b01100 => (Extension::XTheadFMemIdx, Opcode::TH_FLRD, FRD_RS1_RS2_IMM2),
+                0b01110 => (Extension::XTheadFMemIdx, Opcode::TH_FLURD, FRD_RS1_RS2_IMM2),
+                _ => Err(DecodeError::InvalidOpcode)?,
+            },
+            0b111 => match funct5 {
+                0b01000 => (Extension::XTheadFMemIdx, Opcode::TH_FSRW, FRD_RS1_RS2_IMM2),
+                0b01010 => (Extension::XTheadFMemIdx, Opcode::TH_FSURW, FRD_RS1_RS2_IMM2),
+                0b01100 => (Extension::XTheadFMemIdx, Opcode::TH_FSRD, FRD_RS1_RS2_IMM2),
+                0b01110 => (Extension::XTheadFMemIdx, Opcode::TH_FSURD, FRD_RS1_RS2_IMM2),
+                _ => Err(DecodeError::InvalidOpcode)?,
+            },
+            _ => unreachable!("funct3 is three bits"),
+        };
+
+        if self.xlen == Xlen::X32 && is_rv64_only(&opcode) {
+            Err(DecodeError::InvalidOpcode)?
+        }
+        if !self.has_extension(extension) {
+            Err(DecodeError::ExtensionNotEnabled(extension))?
+        }
+
+        // base-updating loads may not also write the base register, and paired loads and stores
+        // must name two distinct registers other than the base.
+        if funct3 == 0b100 && operands == RD_BASE_SIMM5_IMM2 && rd == rs1 {
+            Err(DecodeError::InvalidOperand)?
+        }
+        if operands == RD_RS2_BASE_IMM2_SHIFT && (rd == rs2 || rd == rs1 || rs2 == rs1) {
+            Err(DecodeError::InvalidOperand)?
+        }
+
+        instruction.opcode = opcode;
+        instruction.operands = operands;
+
+        Ok(())
+    }
+}
+
+/// Whether `opcode` is one of the word operations, or loads and stores of doublewords and
+/// zero-extended words, that T-Head only defines for RV64.
+fn is_rv64_only(opcode: &Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::TH_SRRIW
+            | Opcode::TH_REVW
+            | Opcode::TH_MULAW
+            | Opcode::TH_MULSW
+            | Opcode::TH_LRD
+            | Opcode::TH_LURD
+            | Opcode::TH_LDIA
+            | Opcode::TH_LDIB
+            | Opcode::TH_LRWU
+            | Opcode::TH_LURWU
+            | Opcode::TH_LWUIA
+            | Opcode::TH_LWUIB
+            | Opcode::TH_SRD
+            | Opcode::TH_SURD
+            | Opcode::TH_SDIA
+            | Opcode::TH_SDIB
+            | Opcode::TH_LDD
+            | Opcode::TH_SDD
+            | Opcode::TH_LWUD
+    )
+}
@@ -1,8 +1,16 @@
 extern crate yaxpeax_arch;
-extern crate yaxpeax_mips;
+extern crate yaxpeax_riscv;
 
-use yaxpeax_arch::{Arch, Decoder, U8Reader};
-use yaxpeax_mips::{Instruction, RISCV}; //, Opcode};
+use yaxpeax_arch::{
+    Arch, Decoder, Instruction as _, LengthedInstruction, NoColors, ShowContextual, U8Reader,
+};
+use yaxpeax_riscv::{
+    arch_attribute, instruction_length, AttributesError, BufferSink, ColorSink, CustomDecoder,
+    CustomInstruction, CustomSpace, DecodeError, DisplayContext, DisplayOptions, Extension,
+    Extensions, HintClass, Instruction, IsaStringError, Listing, Opcode, Operand, Palette, Radix,
+    Register, RegisterNames, RiscVDecoder, Signedness, Style, Symbolizer, Syntax, Token, TokenKind,
+    UpperImmediate, Xlen, RISCV,
+};
 
 #[allow(dead_code)]
 fn test_decode(data: [u8; 4], expected: Instruction) {
@@ -23,10 +31,12 @@ fn test_decode(data: [u8; 4], expected: Instruction) {
 }
 
 fn test_display(data: [u8; 4], expected: &'static str) {
+    test_display_with(&<RISCV as Arch>::Decoder::default(), data, expected);
+}
+
+fn test_display_with(decoder: &RiscVDecoder, data: [u8; 4], expected: &'static str) {
     let mut reader = U8Reader::new(&data[..]);
-    let instr = <RISCV as Arch>::Decoder::default()
-        .decode(&mut reader)
-        .unwrap();
+    let instr = decoder.decode(&mut reader).unwrap();
     let text = format!("{}", instr);
     assert!(
         text == expected,
@@ -37,20 +47,64 @@ fn test_display(data: [u8; 4], expected: &'static str) {
     );
 }
 
+fn test_display_options(
+    decoder: &RiscVDecoder,
+    options: DisplayOptions,
+    data: [u8; 4],
+    expected: &'static str,
+) {
+    let instr = decode_with(decoder, data);
+    let text = format!("{}", instr.display_with(options));
+    assert!(
+        text == expected,
+        "display error for {:02x}{:02x}{:02x}{:02x} with {:?}:\n  decoded: {:?}\n displayed: {}\n expected: {}\n",
+        data[0], data[1], data[2], data[3],
+        options,
+        instr,
+        text, expected
+    );
+}
+
+fn decode(data: [u8; 4]) -> Instruction {
+    decode_with(&<RISCV as Arch>::Decoder::default(), data)
+}
+
+fn decode_with(decoder: &RiscVDecoder, data: [u8; 4]) -> Instruction {
+    decoder.decode(&mut U8Reader::new(&data[..])).unwrap()
+}
+
+fn test_invalid(data: [u8; 4]) {
+    test_invalid_with(&<RISCV as Arch>::Decoder::default(), data);
+}
+
+fn test_invalid_with(decoder: &RiscVDecoder, data: [u8; 4]) {
+    let mut reader = U8Reader::new(&data[..]);
+    let res = decoder.decode(&mut reader);
+    assert!(
+        res.is_err(),
+        "expected decode error for {:02x}{:02x}{:02x}{:02x}:\n  decoded: {:?}\n",
+        data[0],
+        data[1],
+        data[2],
+        data[3],
+        res
+    );
+}
+
 #[test]
 fn test_arithmetic() {
-    test_display([0x13, 0x06, 0xc6, 0xfb], "addi a2, a2, -0x44");
-    test_display([0x13, 0x01, 0x01, 0xed], "addi sp, sp, -0x130");
-    test_display([0x97, 0x31, 0x88, 0x02], "auipc gp, 0x2883");
-    test_display([0x33, 0x65, 0xb5, 0x00], "or a0, a0, a1");
-    test_display([0x13, 0x56, 0xc5, 0x00], "srli a2, a0, 0xc");
-    test_display([0x13, 0x97, 0x27, 0x00], "slli a4, a5, 0x2");
+    test_display([0x13, 0x06, 0xc6, 0xfb], "addi\ta2,a2,-68");
+    test_display([0x13, 0x01, 0x01, 0xed], "addi\tsp,sp,-304");
+    test_display([0x97, 0x31, 0x88, 0x02], "auipc\tgp,0x2883");
+    test_display([0x33, 0x65, 0xb5, 0x00], "or\ta0,a0,a1");
+    test_display([0x13, 0x56, 0xc5, 0x00], "srli\ta2,a0,0xc");
+    test_display([0x13, 0x97, 0x27, 0x00], "slli\ta4,a5,0x2");
 }
 #[test]
 fn test_br() {
-    test_display([0x63, 0x1a, 0xf7, 0x00], "bne a4, a5, $+0x14");
-    test_display([0x63, 0x08, 0xf7, 0x00], "beq a4, a5, $+0x10");
-    test_display([0xe3, 0x96, 0x07, 0xfe], "bne a5, zero, $-0x14");
+    test_display([0x63, 0x1a, 0xf7, 0x00], "bne\ta4,a5,$+0x14");
+    test_display([0x63, 0x08, 0xf7, 0x00], "beq\ta4,a5,$+0x10");
+    test_display([0xe3, 0x96, 0x07, 0xfe], "bnez\ta5,$-0x14");
     // test_display([0xef, 0x20, 0x40, 0x59], "jal $+0x29f0");
 }
 #[test]
@@ -63,13 +117,1655 @@ fn test_cmp() {
 }
 #[test]
 fn test_mov() {
-    test_display([0xb7, 0x05, 0x00, 0xc0], "lui a1, 0xc0000");
-    test_display([0xb7, 0xc7, 0x29, 0x00], "lui a5, 0x29c");
-    test_display([0x13, 0x85, 0x07, 0x00], "mv a0, a5");
-    test_display([0x23, 0x20, 0xa1, 0x18], "sw a0, 0x180(sp)");
-    test_display([0x03, 0x26, 0x01, 0x18], "lw a2, 0x180(sp)");
+    test_display([0xb7, 0x05, 0x00, 0xc0], "lui\ta1,0xc0000");
+    test_display([0xb7, 0xc7, 0x29, 0x00], "lui\ta5,0x29c");
+    test_display([0x13, 0x85, 0x07, 0x00], "mv\ta0,a5");
+    test_display([0x23, 0x20, 0xa1, 0x18], "sw\ta0,384(sp)");
+    test_display([0x03, 0x26, 0x01, 0x18], "lw\ta2,384(sp)");
 }
 #[test]
 fn test_misc() {
     test_display([0x13, 0x00, 0x00, 0x00], "nop");
 }
+#[test]
+fn test_bf16() {
+    test_display([0x53, 0xf5, 0x85, 0x44], "fcvt.bf16.s\tfa0,fa1");
+    test_display([0x53, 0x95, 0x85, 0x44], "fcvt.bf16.s\tfa0,fa1,rtz");
+    test_display([0x53, 0x75, 0x66, 0x40], "fcvt.s.bf16\tfa0,fa2");
+    test_display([0x57, 0x94, 0x4e, 0x4a], "vfncvtbf16.f.f.w\tv8,v4");
+    test_display([0x57, 0x94, 0x46, 0x48], "vfwcvtbf16.f.f.v\tv8,v4,v0.t");
+    test_display([0x57, 0x14, 0xc2, 0xee], "vfwmaccbf16.vv\tv8,v4,v12");
+    test_display([0x57, 0x54, 0xc5, 0xec], "vfwmaccbf16.vf\tv8,fa0,v12,v0.t");
+    // reserved rounding mode
+    test_invalid([0x53, 0xd5, 0x85, 0x44]);
+}
+#[test]
+fn test_svinval() {
+    test_display([0x73, 0x00, 0xb5, 0x16], "sinval.vma\ta0,a1");
+    test_display([0x73, 0x00, 0x00, 0x18], "sfence.w.inval");
+    test_display([0x73, 0x00, 0x10, 0x18], "sfence.inval.ir");
+    test_display([0x73, 0x00, 0xb5, 0x26], "hinval.vvma\ta0,a1");
+    test_display([0x73, 0x00, 0x05, 0x66], "hinval.gvma\ta0,zero");
+    test_display([0x73, 0x00, 0x00, 0x00], "ecall");
+    test_display([0x73, 0x00, 0x10, 0x00], "ebreak");
+    // sfence.inval.ir with a nonzero rs1
+    test_invalid([0x73, 0x80, 0x12, 0x18]);
+    // sinval.vma with a nonzero rd
+    test_invalid([0xf3, 0x00, 0xb5, 0x16]);
+}
+#[test]
+#[cfg(feature = "p-ext")]
+fn test_packed_simd() {
+    test_display([0x77, 0x85, 0xc5, 0x48], "add8\ta0,a1,a2");
+    test_display([0x77, 0x85, 0xc5, 0x10], "kadd16\ta0,a1,a2");
+    test_display([0x77, 0x85, 0xc5, 0xc8], "smaqa\ta0,a1,a2");
+    test_display([0x77, 0x85, 0xc5, 0xca], "smaqa.su\ta0,a1,a2");
+    test_display([0x77, 0x95, 0xc5, 0x0e], "pkbb16\ta0,a1,a2");
+    test_invalid([0x77, 0x85, 0xc5, 0xfe]);
+}
+#[test]
+#[cfg(not(feature = "p-ext"))]
+fn test_packed_simd_disabled() {
+    test_invalid([0x77, 0x85, 0xc5, 0x48]);
+}
+#[test]
+fn test_thead() {
+    let thead = RiscVDecoder::default().with_extensions(&Extension::THEAD);
+    test_display_with(&thead, [0x0b, 0x95, 0xc5, 0x02], "th.addsl\ta0,a1,a2,1");
+    test_display_with(&thead, [0x0b, 0x95, 0x15, 0x12], "th.srri\ta0,a1,0x21");
+    test_display_with(&thead, [0x0b, 0xa5, 0x05, 0x3c], "th.ext\ta0,a1,15,0");
+    test_display_with(&thead, [0x0b, 0x95, 0x05, 0x86], "th.ff1\ta0,a1");
+    test_display_with(&thead, [0x0b, 0x95, 0x85, 0x8a], "th.tst\ta0,a1,0x28");
+    test_display_with(&thead, [0x0b, 0x95, 0xc5, 0x40], "th.mveqz\ta0,a1,a2");
+    test_display_with(&thead, [0x0b, 0x95, 0xc5, 0x20], "th.mula\ta0,a1,a2");
+    test_display_with(&thead, [0x0b, 0xc5, 0xf5, 0x1d], "th.lbia\ta0,(a1),-1,2");
+    test_display_with(&thead, [0x0b, 0xc5, 0xc5, 0x46], "th.lrw\ta0,a1,a2,3");
+    test_display_with(&thead, [0x0b, 0x45, 0xb6, 0xfa], "th.ldd\ta0,a1,(a2),1,4");
+    test_display_with(&thead, [0x0b, 0x55, 0xb6, 0xe0], "th.swd\ta0,a1,(a2),0,3");
+    test_display_with(&thead, [0x0b, 0xe5, 0xc5, 0x60], "th.flrd\tfa0,a1,a2,0");
+    test_display_with(&thead, [0x0b, 0x00, 0x80, 0x01], "th.sync");
+    test_display_with(&thead, [0x0b, 0x00, 0xb5, 0x04], "th.sfence.vmas\ta0,a1");
+    test_display_with(&thead, [0x0b, 0x00, 0x55, 0x02], "th.dcache.cva\ta0");
+    test_display_with(&thead, [0x0b, 0x00, 0x30, 0x00], "th.dcache.ciall");
+    // base-updating load that also writes its base
+    test_invalid_with(&thead, [0x0b, 0x45, 0xf5, 0x1d]);
+    // paired load writing the same register twice
+    test_invalid_with(&thead, [0x0b, 0x45, 0xa6, 0xfa]);
+
+    // indexed floating-point loads and stores need the registers they move
+    let decode_err = |decoder: &RiscVDecoder, data: [u8; 4]| {
+        decoder.decode(&mut U8Reader::new(&data[..])).unwrap_err()
+    };
+    let single = RiscVDecoder::default()
+        .with_extensions(&Extension::THEAD)
+        .without_extension(Extension::D);
+    test_display_with(&single, [0x0b, 0xe5, 0xc5, 0x40], "th.flrw\tfa0,a1,a2,0");
+    assert_eq!(
+        decode_err(&single, [0x0b, 0xe5, 0xc5, 0x60]),
+        DecodeError::ExtensionNotEnabled(Extension::D)
+    );
+    // doubleword, zero-extended word and word forms are RV64 only
+    let rv32 = RiscVDecoder::default()
+        .with_xlen(Xlen::X32)
+        .with_extensions(&Extension::THEAD);
+    test_display_with(&rv32, [0x0b, 0x55, 0xb6, 0xe0], "th.swd\ta0,a1,(a2),0,3");
+    for (data, rv64) in [
+        ([0x0b, 0x45, 0xb6, 0xfa], "th.ldd\ta0,a1,(a2),1,4"),
+        ([0x0b, 0xc5, 0xc5, 0x66], "th.lrd\ta0,a1,a2,3"),
+        ([0x0b, 0x95, 0x05, 0x90], "th.revw\ta0,a1"),
+    ] {
+        test_display_with(&thead, data, rv64);
+        test_invalid_with(&rv32, data);
+    }
+
+    // vendor encodings are only decoded on request, one extension at a time
+    test_invalid([0x0b, 0x95, 0xc5, 0x02]);
+    let ba = RiscVDecoder::default().with_extension(Extension::XTheadBa);
+    test_display_with(&ba, [0x0b, 0x95, 0xc5, 0x02], "th.addsl\ta0,a1,a2,1");
+    test_invalid_with(&ba, [0x0b, 0x95, 0x15, 0x12]);
+}
+#[test]
+fn test_corev() {
+    let corev = RiscVDecoder::default().with_extensions(&Extension::COREV);
+    test_display_with(&corev, [0x0b, 0x85, 0x45, 0x00], "cv.lb\ta0,(a1),4");
+    test_display_with(&corev, [0x0b, 0xd5, 0xe5, 0xff], "cv.lhu\ta0,(a1),-2");
+    test_display_with(&corev, [0x2b, 0xa4, 0xa5, 0x00], "cv.sw\ta0,(a1),8");
+    test_display_with(&corev, [0x2b, 0xb5, 0xc5, 0x04], "cv.lw\ta0,(a1),a2");
+    test_display_with(&corev, [0x2b, 0xb5, 0xc5, 0x18], "cv.lbu\ta0,a2(a1)");
+    test_display_with(&corev, [0x2b, 0xb6, 0xa5, 0x22], "cv.sh\ta0,(a1),a2");
+    test_display_with(&corev, [0x2b, 0xb6, 0xa5, 0x28], "cv.sb\ta0,a2(a1)");
+    test_display_with(&corev, [0x2b, 0xb5, 0xc5, 0x90], "cv.mac\ta0,a1,a2");
+    test_display_with(&corev, [0x2b, 0xb5, 0x55, 0x70], "cv.clip\ta0,a1,5");
+    test_display_with(&corev, [0x2b, 0xb5, 0x05, 0x64], "cv.extbs\ta0,a1");
+    test_display_with(&corev, [0x2b, 0x40, 0x00, 0x08], "cv.starti\t0,$+0x100");
+    test_display_with(&corev, [0xab, 0x46, 0xa8, 0x00], "cv.setupi\t1,10,$+0x20");
+    test_display_with(&corev, [0xab, 0x45, 0x05, 0x00], "cv.count\t1,a0");
+    test_display_with(&corev, [0x7b, 0x85, 0xc5, 0x00], "cv.add.h\ta0,a1,a2");
+    test_display_with(&corev, [0x7b, 0xd5, 0xc5, 0x30], "cv.max.sc.b\ta0,a1,a2");
+    test_display_with(&corev, [0x7b, 0x95, 0x05, 0x70], "cv.abs.b\ta0,a1");
+    // cv.extbs with a nonzero rs2
+    test_invalid_with(&corev, [0x2b, 0xb5, 0x15, 0x64]);
+    // cv.count with a nonzero immediate
+    test_invalid_with(&corev, [0xab, 0x45, 0x45, 0x00]);
+    // custom opcodes are not decoded unless CORE-V is enabled
+    test_invalid([0x0b, 0x85, 0x45, 0x00]);
+    test_invalid([0x2b, 0xb5, 0xc5, 0x90]);
+
+    // custom-0 is shared with T-Head, and each vendor's encodings decode with both enabled
+    let both = RiscVDecoder::default()
+        .with_extensions(&Extension::THEAD)
+        .with_extensions(&Extension::COREV);
+    test_display_with(&both, [0x0b, 0x85, 0x45, 0x00], "cv.lb\ta0,(a1),4");
+    test_display_with(&both, [0x0b, 0xb5, 0x05, 0x3c], "th.extu\ta0,a1,15,0");
+    test_display_with(&both, [0x0b, 0x00, 0x80, 0x01], "th.sync");
+    let cv_lb = RiscVDecoder::default()
+        .with_extensions(&Extension::THEAD)
+        .with_extension(Extension::XCVmem);
+    test_display_with(&cv_lb, [0x0b, 0x85, 0x45, 0x00], "cv.lb\ta0,(a1),4");
+    // and errors name the vendor whose table the word is in
+    let decode_err = |decoder: &RiscVDecoder, data: [u8; 4]| {
+        decoder.decode(&mut U8Reader::new(&data[..])).unwrap_err()
+    };
+    let decoder = RiscVDecoder::default();
+    assert_eq!(
+        decode_err(&decoder, [0x0b, 0xb5, 0x05, 0x3c]),
+        DecodeError::ExtensionNotEnabled(Extension::XTheadBb)
+    );
+    assert_eq!(
+        decode_err(&decoder, [0x0b, 0x85, 0x45, 0x00]),
+        DecodeError::ExtensionNotEnabled(Extension::XCVmem)
+    );
+    assert_eq!(
+        decode_err(&corev, [0x0b, 0xb5, 0x05, 0x3c]),
+        DecodeError::ExtensionNotEnabled(Extension::XTheadBb)
+    );
+    // th.addsl and cv.lh share an encoding, so with neither enabled it is no one's
+    assert_eq!(
+        decode_err(&decoder, [0x0b, 0x95, 0xc5, 0x02]),
+        DecodeError::InvalidOpcode
+    );
+    let alu = RiscVDecoder::default().with_extension(Extension::XCValu);
+    assert_eq!(
+        decode_err(&alu, [0x0b, 0x95, 0xc5, 0x02]),
+        DecodeError::ExtensionNotEnabled(Extension::XCVmem)
+    );
+
+    let data = [0x0b, 0x85, 0x45, 0x00];
+    let instr = corev.decode(&mut U8Reader::new(&data[..])).unwrap();
+    assert_eq!(
+        instr.operands(),
+        vec![
+            Some(Operand::Reg(10)),
+            Some(Operand::PostIncrement(11, 4)),
+            None,
+            None,
+            None
+        ]
+    );
+}
+/// A toy accelerator with a single multiply-accumulate in custom-2.
+struct Accelerator;
+
+impl CustomDecoder for Accelerator {
+    fn decode(&self, space: CustomSpace, word: u32) -> Option<CustomInstruction> {
+        if space != CustomSpace::Custom2 || word >> 25 != 0b000_0001 {
+            return None;
+        }
+
+        Some(
+            CustomInstruction::new("acc.mac")
+                .with_operand(Operand::Reg(((word >> 7) & 0b11111) as u8))
+                .with_operand(Operand::Reg(((word >> 15) & 0b11111) as u8))
+                .with_operand(Operand::Reg(((word >> 20) & 0b11111) as u8)),
+        )
+    }
+}
+
+#[test]
+fn test_custom_decoder() {
+    let acc = RiscVDecoder::default().with_custom_decoder(Accelerator);
+    test_display_with(&acc, [0x5b, 0x85, 0xc5, 0x02], "acc.mac\ta0,a1,a2");
+    test_invalid_with(&acc, [0x5b, 0x85, 0xc5, 0x04]);
+    test_invalid([0x5b, 0x85, 0xc5, 0x02]);
+
+    let data = [0x5b, 0x85, 0xc5, 0x02];
+    let instr = acc.decode(&mut U8Reader::new(&data[..])).unwrap();
+    assert_eq!(instr.opcode(), &Opcode::Custom("acc.mac"));
+    assert_eq!(
+        instr.operands(),
+        vec![
+            Some(Operand::Reg(10)),
+            Some(Operand::Reg(11)),
+            Some(Operand::Reg(12)),
+            None,
+            None
+        ]
+    );
+
+    // words the custom decoder declines still reach enabled vendor extensions
+    let both = RiscVDecoder::default()
+        .with_extensions(&Extension::COREV)
+        .with_custom_decoder(Accelerator);
+    test_display_with(&both, [0x0b, 0x85, 0x45, 0x00], "cv.lb\ta0,(a1),4");
+    test_display_with(&both, [0x5b, 0x85, 0xc5, 0x02], "acc.mac\ta0,a1,a2");
+}
+#[test]
+fn test_long_instructions() {
+    let data = [
+        // 48-bit
+        0x1f, 0x00, 0x11, 0x22, 0x33, 0x44, //
+        // 64-bit
+        0x3f, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, //
+        // 80-bit
+        0x7f, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, //
+        // 96-bit
+        0x7f, 0x10, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, //
+        // addi a2, a2, -0x44
+        0x13, 0x06, 0xc6, 0xfb,
+    ];
+    let decoder = <RISCV as Arch>::Decoder::default();
+    let mut reader = U8Reader::new(&data[..]);
+    let expected: [(u32, &str); 5] = [
+        (6, ".insn\t6, 0x44332211001f"),
+        (8, ".insn\t8, 0x665544332211003f"),
+        (10, ".half\t0x7f, 0x2211, 0x4433, 0x6655, 0x8877"),
+        (12, ".half\t0x107f, 0x2211, 0x4433, 0x6655, 0x8877, 0xaa99"),
+        (4, "addi\ta2,a2,-68"),
+    ];
+    for (len, text) in expected.iter() {
+        let instr = decoder.decode(&mut reader).unwrap();
+        assert_eq!(instr.len().to_const(), *len);
+        assert_eq!(format!("{}", instr), *text);
+    }
+
+    // every parcel of a long instruction is kept
+    let instr = decoder.decode(&mut U8Reader::new(&data[24..])).unwrap();
+    assert_eq!(instr.long_word(), 0x6655_4433_2211_107f);
+    assert_eq!(instr.parcel(5), Some(0xaa99));
+    assert_eq!(instr.parcel(6), None);
+
+    // truncated 48-bit instruction
+    let mut reader = U8Reader::new(&data[..4]);
+    assert!(decoder.decode(&mut reader).is_err());
+    // reserved >= 192-bit encoding
+    let mut reader = U8Reader::new(&[0x7f, 0x70, 0, 0][..]);
+    assert!(decoder.decode(&mut reader).is_err());
+}
+#[test]
+fn test_instruction_length() {
+    assert_eq!(instruction_length(0x0001), Some(2));
+    assert_eq!(instruction_length(0xfffe), Some(2));
+    assert_eq!(instruction_length(0x0613), Some(4));
+    assert_eq!(instruction_length(0x001f), Some(6));
+    assert_eq!(instruction_length(0x005f), Some(6));
+    assert_eq!(instruction_length(0x003f), Some(8));
+    assert_eq!(instruction_length(0x007f), Some(10));
+    assert_eq!(instruction_length(0x607f), Some(22));
+    assert_eq!(instruction_length(0x707f), None);
+    assert_eq!(
+        <RISCV as Arch>::Decoder::default().instruction_length(0x001f),
+        Some(6)
+    );
+}
+#[test]
+fn test_isa_string() {
+    let decoder = RiscVDecoder::from_isa_string("rv64imafdc_zicsr_zifencei_zba_zbb").unwrap();
+    assert_eq!(decoder.xlen(), Xlen::X64);
+    for ext in [
+        Extension::I,
+        Extension::C,
+        Extension::Zba,
+        Extension::Zmmul,
+        Extension::Zca,
+    ] {
+        assert!(decoder.has_extension(ext), "{} should be enabled", ext);
+    }
+    assert!(!decoder.has_extension(Extension::V));
+    assert!(!decoder.has_extension(Extension::Zfbfmin));
+
+    let decoder = RiscVDecoder::from_isa_string("RV32I2p1M2_Zfbfmin1p0").unwrap();
+    assert_eq!(decoder.xlen(), Xlen::X32);
+    assert!(decoder.has_extension(Extension::M));
+    assert!(decoder.has_extension(Extension::F));
+    assert!(decoder.has_extension(Extension::Zicsr));
+
+    let decoder = RiscVDecoder::from_isa_string("rv64gcp").unwrap();
+    assert!(decoder.has_extension(Extension::Zifencei));
+    assert!(decoder.has_extension(Extension::P));
+
+    let decoder = RiscVDecoder::from_isa_string("rva23u64_zvfbfwma").unwrap();
+    assert!(decoder.has_extension(Extension::V));
+    assert!(decoder.has_extension(Extension::Zba));
+    assert!(decoder.has_extension(Extension::Zvfbfmin));
+    assert!(!decoder.has_extension(Extension::Svinval));
+    // the bfloat16 vector conversions need a vector unit
+    let decoder = RiscVDecoder::from_isa_string("rv64i_zvfbfmin").unwrap();
+    assert!(decoder.has_extension(Extension::Zve32f));
+    assert!(decoder.has_extension(Extension::F));
+    assert!(!decoder.has_extension(Extension::Zve64x));
+    let decoder = RiscVDecoder::from_isa_string("rva22u64").unwrap();
+    assert!(decoder.has_extension(Extension::Zicboz));
+    assert!(!decoder.has_extension(Extension::V));
+
+    assert_eq!(
+        RiscVDecoder::from_isa_string("x86_64").unwrap_err(),
+        IsaStringError::InvalidBase
+    );
+    assert_eq!(
+        RiscVDecoder::from_isa_string("rv32e").unwrap_err(),
+        IsaStringError::UnsupportedBase
+    );
+    assert_eq!(
+        RiscVDecoder::from_isa_string("rv64i_zfoo").unwrap_err(),
+        IsaStringError::UnknownExtension("zfoo".to_string())
+    );
+
+    // vendor extensions may be named too
+    let decoder = RiscVDecoder::from_isa_string("rv64gc_xtheadba").unwrap();
+    test_display_with(&decoder, [0x0b, 0x95, 0xc5, 0x02], "th.addsl\ta0,a1,a2,1");
+}
+
+#[test]
+fn test_extension_not_enabled() {
+    let decoder = RiscVDecoder::from_isa_string("rv64i").unwrap();
+    test_display_with(&decoder, [0x13, 0x05, 0x10, 0x00], "li\ta0,1");
+
+    // pause is a fence hint that any base-I core runs
+    test_display_with(&decoder, [0x0f, 0x00, 0x00, 0x01], "fence\tw,0");
+
+    let data = [0x53, 0xf5, 0x85, 0x44];
+    assert_eq!(
+        decoder.decode(&mut U8Reader::new(&data[..])).unwrap_err(),
+        DecodeError::ExtensionNotEnabled(Extension::Zfbfmin)
+    );
+    let data = [0x73, 0x00, 0xb5, 0x16];
+    assert_eq!(
+        decoder.decode(&mut U8Reader::new(&data[..])).unwrap_err(),
+        DecodeError::ExtensionNotEnabled(Extension::Svinval)
+    );
+    let data = [0x01, 0x00];
+    assert_eq!(
+        decoder.decode(&mut U8Reader::new(&data[..])).unwrap_err(),
+        DecodeError::ExtensionNotEnabled(Extension::Zca)
+    );
+    let data = [0x0b, 0x95, 0xc5, 0x02];
+    assert_eq!(
+        RiscVDecoder::default()
+            .with_extension(Extension::XTheadBb)
+            .decode(&mut U8Reader::new(&data[..]))
+            .unwrap_err(),
+        DecodeError::ExtensionNotEnabled(Extension::XTheadBa)
+    );
+
+    // standard extensions can be switched off individually
+    let decoder = RiscVDecoder::default().without_extension(Extension::Zvfbfwma);
+    test_display([0x57, 0x14, 0xc2, 0xee], "vfwmaccbf16.vv\tv8,v4,v12");
+    test_invalid_with(&decoder, [0x57, 0x14, 0xc2, 0xee]);
+}
+
+/// Build a `.riscv.attributes` section holding one `Tag_File` subsection per vendor.
+fn attributes_section(vendors: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut section = vec![b'A'];
+    for (vendor, attributes) in vendors {
+        let file_len = 1 + 4 + attributes.len() as u32;
+        let len = 4 + vendor.len() as u32 + 1 + file_len;
+        section.extend_from_slice(&len.to_le_bytes());
+        section.extend_from_slice(vendor.as_bytes());
+        section.push(0);
+        section.push(1);
+        section.extend_from_slice(&file_len.to_le_bytes());
+        section.extend_from_slice(attributes);
+    }
+    section
+}
+
+#[test]
+fn test_attributes() {
+    let mut attributes = vec![
+        // Tag_RISCV_stack_align = 16
+        4, 16,
+    ];
+    attributes.push(5);
+    attributes.extend_from_slice(
+        b"rv64i2p1_m2p0_a2p1_f2p2_d2p2_c2p0_zicsr2p0_zifencei2p0_zmmul1p0_zaamo1p0_zalrsc1p0_zca1p0_zcd1p0_zvl32b1p0\0",
+    );
+    // Tag_RISCV_unaligned_access = 0, Tag_RISCV_priv_spec = 1
+    attributes.extend_from_slice(&[6, 0, 8, 1]);
+
+    let section = attributes_section(&[("gnu", &[32, 0x80, 0x01]), ("riscv", &attributes)]);
+    assert_eq!(
+        arch_attribute(&section),
+        Ok("rv64i2p1_m2p0_a2p1_f2p2_d2p2_c2p0_zicsr2p0_zifencei2p0_zmmul1p0_zaamo1p0_zalrsc1p0_zca1p0_zcd1p0_zvl32b1p0")
+    );
+    let decoder = RiscVDecoder::from_attributes(&section).unwrap();
+    assert_eq!(decoder.xlen(), Xlen::X64);
+    assert!(decoder.has_extension(Extension::Zcd));
+    assert!(!decoder.has_extension(Extension::V));
+
+    let section = attributes_section(&[("riscv", &[4, 16, 5, b'r', b'v', b'3', b'2', b'i', 0])]);
+    let decoder = RiscVDecoder::from_attributes(&section).unwrap();
+    assert_eq!(decoder.xlen(), Xlen::X32);
+    assert!(!decoder.has_extension(Extension::M));
+
+    let section = attributes_section(&[("riscv", &[4, 16])]);
+    assert_eq!(
+        RiscVDecoder::from_attributes(&section).unwrap_err(),
+        AttributesError::MissingArch
+    );
+    let section = attributes_section(&[("riscv", &[5, b'r', b'v', b'6', b'4', b'z', 0])]);
+    assert_eq!(
+        RiscVDecoder::from_attributes(&section).unwrap_err(),
+        AttributesError::Isa(IsaStringError::InvalidBase)
+    );
+    assert_eq!(
+        arch_attribute(&section[..section.len() - 3]),
+        Err(AttributesError::Malformed)
+    );
+    assert_eq!(arch_attribute(b"B"), Err(AttributesError::Malformed));
+}
+
+#[test]
+fn test_extensions_used() {
+    let decoder = RiscVDecoder::default().with_extension(Extension::XTheadBa);
+    let data = [
+        0x13, 0x05, 0x10, 0x00, // addi a0, zero, 0x1
+        0x53, 0xf5, 0x85, 0x44, // fcvt.bf16.s fa0, fa1
+        0x73, 0x00, 0xb5, 0x26, // hinval.vvma a0, a1
+        0x0b, 0x95, 0xc5, 0x02, // th.addsl a0, a1, a2, 0x1
+    ];
+    let mut reader = U8Reader::new(&data[..]);
+    let instrs: Vec<Instruction> = (0..4)
+        .map(|_| decoder.decode(&mut reader).unwrap())
+        .collect();
+
+    assert_eq!(
+        instrs[0].extensions().iter().collect::<Vec<_>>(),
+        [Extension::I]
+    );
+    assert_eq!(
+        instrs[2].extensions().iter().collect::<Vec<_>>(),
+        [Extension::H, Extension::Svinval]
+    );
+    assert_eq!(
+        Extensions::used_by(&instrs).iter().collect::<Vec<_>>(),
+        [
+            Extension::I,
+            Extension::H,
+            Extension::Zfbfmin,
+            Extension::Svinval,
+            Extension::XTheadBa
+        ]
+    );
+    assert!(Opcode::Opaque.extensions().is_empty());
+}
+
+#[test]
+fn test_decode_errors() {
+    fn error(decoder: &RiscVDecoder, data: &[u8]) -> DecodeError {
+        decoder.decode(&mut U8Reader::new(data)).unwrap_err()
+    }
+    let rv64 = RiscVDecoder::default();
+    let rv32 = RiscVDecoder::default().with_xlen(Xlen::X32);
+
+    // truncated 32-bit instruction
+    assert_eq!(error(&rv64, &[0x13, 0x05]), DecodeError::ExhaustedInput);
+    // slli with a funct6 nothing claims
+    assert_eq!(
+        error(&rv64, &[0x13, 0x95, 0x35, 0x04]),
+        DecodeError::InvalidOpcode
+    );
+    // fcvt.bf16.s with a reserved rounding mode
+    assert_eq!(
+        error(&rv64, &[0x53, 0xd5, 0x85, 0x44]),
+        DecodeError::Reserved
+    );
+    // shamt[5] set in RV32
+    assert_eq!(
+        error(&rv32, &[0x13, 0x95, 0x05, 0x02]),
+        DecodeError::Reserved
+    );
+    test_display([0x13, 0x95, 0x05, 0x02], "slli\ta0,a1,0x20");
+    // reserved major opcode, and the >= 192-bit encoding
+    assert_eq!(
+        error(&rv64, &[0x6b, 0x00, 0x00, 0x00]),
+        DecodeError::Reserved
+    );
+    assert_eq!(
+        error(&rv64, &[0x7f, 0x70, 0x00, 0x00]),
+        DecodeError::Reserved
+    );
+    // c.nop 0x1
+    assert_eq!(error(&rv64, &[0x05, 0x00]), DecodeError::Hint);
+    // mul, cbo.clean, wfi and c.nop
+    assert_eq!(
+        error(&rv64, &[0x33, 0x85, 0xc5, 0x02]),
+        DecodeError::Unimplemented
+    );
+    assert_eq!(
+        error(&rv64, &[0x0f, 0x20, 0x15, 0x00]),
+        DecodeError::Unimplemented
+    );
+    assert_eq!(
+        error(&rv64, &[0x73, 0x00, 0x50, 0x10]),
+        DecodeError::Unimplemented
+    );
+    assert_eq!(error(&rv64, &[0x01, 0x00]), DecodeError::Unimplemented);
+    // ld is only an instruction in RV64
+    test_display([0x03, 0xb5, 0x85, 0x00], "ld\ta0,8(a1)");
+    assert_eq!(
+        error(&rv32, &[0x03, 0xb5, 0x85, 0x00]),
+        DecodeError::InvalidOpcode
+    );
+}
+
+#[test]
+fn test_shift_amounts() {
+    // the shift amount is the 6-bit field in the rs2 slot, not the low bits of the I immediate
+    let thead = RiscVDecoder::default().with_extensions(&Extension::THEAD);
+    let shift = |data: [u8; 4]| decode_with(&thead, data).operands()[2];
+    // srai a0, a1, 63
+    assert_eq!(shift([0x13, 0xd5, 0xf5, 0x43]), Some(Operand::Shift(63)));
+    // slli a0, a1, 32
+    assert_eq!(shift([0x13, 0x95, 0x05, 0x02]), Some(Operand::Shift(32)));
+    // sraiw a0, a1, 31
+    assert_eq!(shift([0x1b, 0xd5, 0xf5, 0x41]), Some(Operand::Shift(31)));
+    // th.tst a0, a1, 0x28, whose funct6 sets bit 7 of the I immediate
+    assert_eq!(shift([0x0b, 0x95, 0x85, 0x8a]), Some(Operand::Shift(0x28)));
+    test_display([0x13, 0xd5, 0xf5, 0x43], "srai\ta0,a1,0x3f");
+}
+
+#[test]
+fn test_fences_and_jalr() {
+    // fences decode, so that their HINT forms can be classified and their reserved forms caught
+    test_display([0x0f, 0x00, 0xf0, 0x0f], "fence");
+    // with rd or rs1 set, the full form
+    test_display([0x8f, 0x00, 0xf0, 0x0f], "fence\tiorw,iorw");
+    test_display([0x0f, 0x80, 0xf0, 0x0f], "fence\tiorw,iorw");
+    test_display([0x0f, 0x00, 0x10, 0x03], "fence\trw,w");
+    test_display([0x0f, 0x00, 0x30, 0x83], "fence.tso");
+    test_display([0x0f, 0x10, 0x00, 0x00], "fence.i");
+    test_display([0x0f, 0x00, 0x00, 0x01], "pause");
+    // jalr with a nonzero funct3 is not an instruction
+    test_invalid([0x67, 0x90, 0x00, 0x00]);
+}
+
+#[test]
+fn test_hints_and_well_defined() {
+    assert_eq!(decode([0x13, 0x00, 0x00, 0x00]).hint(), None);
+    assert_eq!(decode([0x13, 0x05, 0x10, 0x00]).hint(), None);
+    assert_eq!(
+        decode([0x13, 0x80, 0x50, 0x00]).hint(),
+        Some(HintClass::Standard)
+    );
+    assert_eq!(
+        decode([0x37, 0x00, 0x00, 0x45]).hint(),
+        Some(HintClass::Standard)
+    );
+    assert_eq!(
+        decode([0x13, 0xa0, 0x50, 0x00]).hint(),
+        Some(HintClass::Custom)
+    );
+    assert_eq!(
+        decode([0x13, 0x60, 0x15, 0x04]).hint(),
+        Some(HintClass::Prefetch)
+    );
+    assert_eq!(
+        decode([0x33, 0x00, 0x50, 0x00]).hint(),
+        Some(HintClass::NonTemporal)
+    );
+    assert_eq!(
+        decode([0x0f, 0x00, 0x00, 0x01]).hint(),
+        Some(HintClass::Pause)
+    );
+    assert_eq!(
+        decode([0x0f, 0x00, 0x10, 0x00]).hint(),
+        Some(HintClass::Standard)
+    );
+    assert_eq!(decode([0x0f, 0x00, 0xf0, 0x0f]).hint(), None);
+
+    // compressed HINTs, c.nop 0x1 and c.li zero, 0x1
+    let decoder = RiscVDecoder::default();
+    for data in [[0x05, 0x00], [0x05, 0x40]] {
+        assert_eq!(
+            decoder.decode(&mut U8Reader::new(&data[..])).unwrap_err(),
+            DecodeError::Hint
+        );
+    }
+
+    assert!(decode([0x0f, 0x00, 0xf0, 0x0f]).well_defined());
+    assert!(decode([0x0f, 0x00, 0x30, 0x83]).well_defined());
+    assert!(decode([0x13, 0x80, 0x50, 0x00]).well_defined());
+    // fence with rd set, or a reserved fm
+    assert!(!decode([0x0f, 0x05, 0xf0, 0x0f]).well_defined());
+    assert!(!decode([0x0f, 0x00, 0xf0, 0x4f]).well_defined());
+    // fence.i with an immediate
+    assert!(!decode([0x0f, 0x10, 0x10, 0x00]).well_defined());
+    // masked operation writing v0, and widening operations overwriting a source
+    assert!(!decode([0x57, 0x90, 0x46, 0x48]).well_defined());
+    assert!(!decode([0x57, 0x12, 0xc2, 0xee]).well_defined());
+    assert!(decode([0x57, 0x94, 0x46, 0x4a]).well_defined());
+}
+
+#[test]
+fn test_pseudo_instructions() {
+    // jumps
+    test_display([0x67, 0x80, 0x00, 0x00], "ret");
+    test_display([0x67, 0x80, 0x07, 0x00], "jr\ta5");
+    test_display([0x67, 0x80, 0x87, 0x00], "jr\t8(a5)");
+    test_display([0xe7, 0x80, 0x07, 0x00], "jalr\ta5");
+    test_display([0xe7, 0x82, 0x07, 0x00], "jalr\tt0,a5");
+    test_display([0x6f, 0xf0, 0x1f, 0xf0], "j\t$-0x100");
+    test_display([0xef, 0x00, 0x10, 0x00], "jal\t$+0x800");
+    test_display([0xef, 0x02, 0x00, 0x01], "jal\tt0,$+0x10");
+
+    // integer aliases, including the RV64I word forms
+    test_display([0x13, 0x00, 0x00, 0x00], "nop");
+    test_display([0x13, 0x05, 0xf0, 0xff], "li\ta0,-1");
+    test_display([0x13, 0x85, 0x05, 0x00], "mv\ta0,a1");
+    test_display([0x13, 0xf5, 0xf5, 0x0f], "zext.b\ta0,a1");
+    test_display([0x13, 0xc5, 0xf5, 0xff], "not\ta0,a1");
+    test_display([0x13, 0xb5, 0x15, 0x00], "seqz\ta0,a1");
+    test_display([0x33, 0x05, 0xb0, 0x40], "neg\ta0,a1");
+    test_display([0x33, 0x35, 0xb0, 0x00], "snez\ta0,a1");
+    test_display([0x33, 0xa5, 0x05, 0x00], "sltz\ta0,a1");
+    test_display([0x33, 0x25, 0xb0, 0x00], "sgtz\ta0,a1");
+    test_display([0x1b, 0x85, 0x05, 0x00], "sext.w\ta0,a1");
+    test_display([0x3b, 0x05, 0xb0, 0x40], "negw\ta0,a1");
+    test_display([0x1b, 0x85, 0xd5, 0xff], "addiw\ta0,a1,-3");
+    test_display([0x1b, 0xd5, 0xf5, 0x41], "sraiw\ta0,a1,0x1f");
+    test_display([0x3b, 0x85, 0xc5, 0x40], "subw\ta0,a1,a2");
+
+    // branches against zero
+    test_display([0x63, 0x08, 0x05, 0x00], "beqz\ta0,$+0x10");
+    test_display([0xe3, 0x18, 0x05, 0xfe], "bnez\ta0,$-0x10");
+    test_display([0x63, 0x54, 0xa0, 0x00], "blez\ta0,$+0x8");
+    test_display([0x63, 0x54, 0x05, 0x00], "bgez\ta0,$+0x8");
+    test_display([0x63, 0x44, 0x05, 0x00], "bltz\ta0,$+0x8");
+    test_display([0x63, 0x44, 0xa0, 0x00], "bgtz\ta0,$+0x8");
+    test_display([0x63, 0xc4, 0xa5, 0x00], "blt\ta1,a0,$+0x8");
+
+    // RV64I loads and stores
+    test_display([0x03, 0x65, 0xc1, 0xff], "lwu\ta0,-4(sp)");
+    test_display([0x23, 0x3c, 0x11, 0x00], "sd\tra,24(sp)");
+
+    // CSR accesses
+    test_display([0x73, 0x10, 0x00, 0xc0], "unimp");
+    test_display([0x73, 0x25, 0x00, 0xc0], "rdcycle\ta0");
+    test_display([0x73, 0x25, 0x20, 0xc0], "rdinstret\ta0");
+    test_display([0x73, 0x25, 0x00, 0x30], "csrr\ta0,mstatus");
+    test_display([0x73, 0x10, 0x05, 0x18], "csrw\tsatp,a0");
+    test_display([0xf3, 0x15, 0x05, 0x14], "csrrw\ta1,sscratch,a0");
+    test_display([0x73, 0x60, 0x04, 0x30], "csrsi\tmstatus,8");
+    test_display([0x73, 0x75, 0x41, 0x30], "csrrci\ta0,mie,2");
+    test_display([0x73, 0x25, 0x00, 0x7c], "csrr\ta0,0x7c0");
+    test_display([0x73, 0x25, 0x20, 0x00], "frrm\ta0");
+    test_display([0x73, 0x10, 0x15, 0x00], "fsflags\ta0");
+    test_display([0xf3, 0x15, 0x35, 0x00], "fscsr\ta1,a0");
+    test_display([0x73, 0xd0, 0x20, 0x00], "fsrmi\t1");
+
+    // sign injection
+    test_display([0x53, 0x85, 0xb5, 0x20], "fmv.s\tfa0,fa1");
+    test_display([0x53, 0x95, 0xb5, 0x22], "fneg.d\tfa0,fa1");
+    test_display([0x53, 0xa5, 0xb5, 0x20], "fabs.s\tfa0,fa1");
+    test_display([0x53, 0x85, 0xc5, 0x22], "fsgnj.d\tfa0,fa1,fa2");
+    test_display([0x53, 0xa5, 0xc5, 0x20], "fsgnjx.s\tfa0,fa1,fa2");
+}
+
+#[test]
+fn test_syntax() {
+    // the default options are the plain Display impl
+    let instr = decode([0x13, 0x95, 0x05, 0x02]);
+    assert_eq!(
+        format!("{}", instr.display_with(DisplayOptions::default())),
+        format!("{}", instr)
+    );
+
+    let corev = RiscVDecoder::default().with_extensions(&Extension::COREV);
+    let options = DisplayOptions::new();
+    for (data, objdump, llvm, no_aliases) in [
+        (
+            [0x13, 0x05, 0xf0, 0xff],
+            "li\ta0,-1",
+            "li\ta0, -1",
+            "addi\ta0,zero,-1",
+        ),
+        (
+            [0x13, 0xf5, 0xf5, 0x0f],
+            "zext.b\ta0,a1",
+            "andi\ta0, a1, 255",
+            "andi\ta0,a1,255",
+        ),
+        (
+            [0x13, 0x95, 0x05, 0x02],
+            "slli\ta0,a1,0x20",
+            "slli\ta0, a1, 32",
+            "slli\ta0,a1,0x20",
+        ),
+        (
+            [0xb7, 0xc7, 0x29, 0x00],
+            "lui\ta5,0x29c",
+            "lui\ta5, 668",
+            "lui\ta5,0x29c",
+        ),
+        (
+            [0x73, 0x25, 0x00, 0x7c],
+            "csrr\ta0,0x7c0",
+            "csrr\ta0, 1984",
+            "csrrs\ta0,0x7c0,zero",
+        ),
+        ([0x67, 0x80, 0x00, 0x00], "ret", "ret", "jalr\tzero,0(ra)"),
+        (
+            [0x6f, 0xf0, 0x1f, 0xf0],
+            "j\t$-0x100",
+            "j\t$-0x100",
+            "jal\tzero,$-0x100",
+        ),
+        (
+            [0xe3, 0x18, 0x05, 0xfe],
+            "bnez\ta0,$-0x10",
+            "bnez\ta0, $-0x10",
+            "bne\ta0,zero,$-0x10",
+        ),
+        ([0x13, 0x00, 0x00, 0x00], "nop", "nop", "addi\tzero,zero,0"),
+        (
+            [0x0f, 0x00, 0x10, 0x03],
+            "fence\trw,w",
+            "fence\trw, w",
+            "fence\trw,w",
+        ),
+        (
+            [0x0b, 0x85, 0x45, 0x00],
+            "cv.lb\ta0,(a1),4",
+            "cv.lb\ta0, (a1), 4",
+            "cv.lb\ta0,(a1),4",
+        ),
+    ] {
+        for (syntax, expected) in [
+            (Syntax::Objdump, objdump),
+            (Syntax::Llvm, llvm),
+            (Syntax::NoAliases, no_aliases),
+        ] {
+            test_display_options(&corev, options.with_syntax(syntax), data, expected);
+        }
+    }
+}
+
+#[test]
+fn test_register_names() {
+    let corev = RiscVDecoder::default().with_extensions(&Extension::COREV);
+    let options = DisplayOptions::new();
+    for (data, abi, abi_fp, numeric) in [
+        (
+            [0x13, 0x04, 0x01, 0x01],
+            "addi\ts0,sp,16",
+            "addi\tfp,sp,16",
+            "addi\tx8,x2,16",
+        ),
+        (
+            [0x83, 0x34, 0x84, 0xff],
+            "ld\ts1,-8(s0)",
+            "ld\ts1,-8(fp)",
+            "ld\tx9,-8(x8)",
+        ),
+        (
+            [0x23, 0x3c, 0x11, 0x00],
+            "sd\tra,24(sp)",
+            "sd\tra,24(sp)",
+            "sd\tx1,24(x2)",
+        ),
+        (
+            [0x53, 0xa5, 0xc5, 0x20],
+            "fsgnjx.s\tfa0,fa1,fa2",
+            "fsgnjx.s\tfa0,fa1,fa2",
+            "fsgnjx.s\tf10,f11,f12",
+        ),
+        (
+            [0x2b, 0xb5, 0xc5, 0x18],
+            "cv.lbu\ta0,a2(a1)",
+            "cv.lbu\ta0,a2(a1)",
+            "cv.lbu\tx10,x12(x11)",
+        ),
+        (
+            [0x2b, 0xb6, 0xa5, 0x22],
+            "cv.sh\ta0,(a1),a2",
+            "cv.sh\ta0,(a1),a2",
+            "cv.sh\tx10,(x11),x12",
+        ),
+    ] {
+        for (names, expected) in [
+            (RegisterNames::Abi, abi),
+            (RegisterNames::AbiFp, abi_fp),
+            (RegisterNames::Numeric, numeric),
+        ] {
+            test_display_options(&corev, options.with_register_names(names), data, expected);
+        }
+    }
+
+    // register naming is independent of the syntax
+    test_display_options(
+        &corev,
+        options
+            .with_syntax(Syntax::Llvm)
+            .with_register_names(RegisterNames::Numeric),
+        [0x13, 0x04, 0x01, 0x01],
+        "addi\tx8, x2, 16",
+    );
+}
+
+#[test]
+fn test_immediate_formatting() {
+    let decoder = RiscVDecoder::default();
+    let options = DisplayOptions::new();
+    let addi = [0x13, 0x01, 0x01, 0xed];
+    let sd = [0x23, 0x3c, 0x11, 0x00];
+    let slli = [0x13, 0x95, 0x05, 0x02];
+    let lui = [0x37, 0xf5, 0xff, 0xff];
+
+    test_display_options(&decoder, options, addi, "addi\tsp,sp,-304");
+    test_display_options(&decoder, options, slli, "slli\ta0,a1,0x20");
+    test_display_options(&decoder, options, lui, "lui\ta0,0xfffff");
+
+    let hex = options.with_radix(Radix::Hex);
+    test_display_options(&decoder, hex, addi, "addi\tsp,sp,-0x130");
+    test_display_options(&decoder, hex, sd, "sd\tra,0x18(sp)");
+    test_display_options(&decoder, hex, slli, "slli\ta0,a1,0x20");
+
+    let decimal = options.with_radix(Radix::Decimal);
+    test_display_options(&decoder, decimal, slli, "slli\ta0,a1,32");
+    test_display_options(&decoder, decimal, lui, "lui\ta0,1048575");
+
+    let both = options.with_radix(Radix::Both);
+    test_display_options(&decoder, both, addi, "addi\tsp,sp,-304 (-0x130)");
+    // memory offsets stay one number, so the reference still reads as one
+    test_display_options(&decoder, both, sd, "sd\tra,24(sp)");
+
+    // unsigned values are as wide as the registers
+    let unsigned = options.with_signedness(Signedness::Unsigned);
+    assert_eq!(unsigned.xlen(), Xlen::X64);
+    test_display_options(&decoder, unsigned, addi, "addi\tsp,sp,18446744073709551312");
+    test_display_options(
+        &decoder,
+        unsigned.with_radix(Radix::Hex),
+        addi,
+        "addi\tsp,sp,0xfffffffffffffed0",
+    );
+    test_display_options(
+        &decoder,
+        unsigned.with_radix(Radix::Both),
+        addi,
+        "addi\tsp,sp,18446744073709551312 (0xfffffffffffffed0)",
+    );
+    let rv32 = RiscVDecoder::default().with_xlen(Xlen::X32);
+    let unsigned32 = unsigned.with_xlen(rv32.xlen());
+    test_display_options(&rv32, unsigned32, addi, "addi\tsp,sp,4294966992");
+    test_display_options(
+        &rv32,
+        unsigned32.with_radix(Radix::Hex),
+        addi,
+        "addi\tsp,sp,0xfffffed0",
+    );
+
+    let shifted = options.with_upper_immediate(UpperImmediate::Shifted);
+    test_display_options(&decoder, shifted, lui, "lui\ta0,-0x1000");
+    test_display_options(
+        &decoder,
+        shifted.with_signedness(Signedness::Unsigned),
+        lui,
+        "lui\ta0,0xfffffffffffff000",
+    );
+    test_display_options(
+        &decoder,
+        shifted.with_syntax(Syntax::Llvm),
+        lui,
+        "lui\ta0, -4096",
+    );
+
+    // the radix applies to LLVM syntax too
+    test_display_options(
+        &decoder,
+        options.with_syntax(Syntax::Llvm).with_radix(Radix::Hex),
+        slli,
+        "slli\ta0, a1, 0x20",
+    );
+}
+
+#[test]
+fn test_branch_targets() {
+    let bne = decode([0xe3, 0x18, 0xf7, 0xfe]);
+    let jal = decode([0xef, 0x00, 0x40, 0x2b]);
+    let j = decode([0x6f, 0xf0, 0x9f, 0xff]);
+    let jalr = decode([0xe7, 0x80, 0x07, 0x00]);
+
+    assert_eq!(bne.branch_target(0x100a4), Some(0x10094));
+    assert_eq!(jal.branch_target(0x1009c), Some(0x10350));
+    assert_eq!(j.branch_target(0), Some(0xffff_ffff_ffff_fff8));
+    assert_eq!(jalr.branch_target(0x1000), None);
+    assert_eq!(decode([0x13, 0x00, 0x00, 0x00]).branch_target(0x1000), None);
+
+    let options = DisplayOptions::new();
+    assert_eq!(
+        format!("{}", bne.display_with(options).at(0x100a4)),
+        "bne\ta4,a5,10094"
+    );
+    assert_eq!(
+        format!("{}", jal.display_with(options).at(0x1009c)),
+        "jal\t10350"
+    );
+    assert_eq!(
+        format!(
+            "{}",
+            jal.display_with(options.with_syntax(Syntax::NoAliases))
+                .at(0x1009c)
+        ),
+        "jal\tra,10350"
+    );
+    assert_eq!(
+        format!(
+            "{}",
+            bne.display_with(options.with_syntax(Syntax::Llvm))
+                .at(0x100a4)
+        ),
+        "bne\ta4, a5, 0x10094"
+    );
+    // targets wrap at the register width
+    assert_eq!(
+        format!("{}", j.display_with(options).at(0)),
+        "j\tfffffffffffffff8"
+    );
+    assert_eq!(
+        format!("{}", j.display_with(options.with_xlen(Xlen::X32)).at(0)),
+        "j\tfffffff8"
+    );
+
+    // without a context, contextual display uses the default options
+    let mut out = String::new();
+    bne.contextualize(&NoColors, 0x100a4, None, &mut out)
+        .unwrap();
+    assert_eq!(out, "bne\ta4,a5,10094");
+    let context = DisplayContext::new().with_options(options.with_syntax(Syntax::NoAliases));
+    let mut out = String::new();
+    j.contextualize(&NoColors, 0x2000, Some(&context), &mut out)
+        .unwrap();
+    assert_eq!(out, "jal\tzero,1ff8");
+    // without an address, targets are relative to the instruction
+    assert_eq!(format!("{}", bne), "bne\ta4,a5,$-0x10");
+}
+
+#[test]
+fn test_symbolizer() {
+    struct Symbols(&'static [(&'static str, u64, u64)]);
+
+    impl Symbolizer for Symbols {
+        fn symbolize(&self, address: u64) -> Option<(&str, u64)> {
+            self.0
+                .iter()
+                .find(|(_, start, size)| (*start..start + size).contains(&address))
+                .map(|(name, start, _)| (*name, address - start))
+        }
+    }
+
+    let symbols = Symbols(&[
+        ("main", 0x10080, 0x100),
+        ("memcpy", 0x10350, 0x40),
+        ("global_var", 0x12320, 0x20),
+    ]);
+    let options = DisplayOptions::new();
+    let show = |instr: &Instruction, address: u64| {
+        format!(
+            "{}",
+            instr
+                .display_with(options)
+                .at(address)
+                .with_symbolizer(&symbols)
+        )
+    };
+
+    let jal = decode([0xef, 0x00, 0x40, 0x2b]);
+    let bne = decode([0xe3, 0x18, 0xf7, 0xfe]);
+    assert_eq!(show(&jal, 0x1009c), "jal\t10350 <memcpy>");
+    assert_eq!(show(&bne, 0x100a4), "bne\ta4,a5,10094 <main+0x14>");
+    assert_eq!(show(&bne, 0x4010), "bne\ta4,a5,4000");
+    assert_eq!(
+        format!(
+            "{}",
+            jal.display_with(options.with_syntax(Syntax::Llvm))
+                .at(0x1009c)
+                .with_symbolizer(&symbols)
+        ),
+        "jal\t0x10350 <memcpy>"
+    );
+
+    // contextual display carries options and a symbolizer together
+    let context = DisplayContext::new().with_symbolizer(&symbols);
+    let mut out = String::new();
+    jal.contextualize(&NoColors, 0x1009c, Some(&context), &mut out)
+        .unwrap();
+    assert_eq!(out, "jal\t10350 <memcpy>");
+    let context = context.with_options(options.with_syntax(Syntax::Llvm));
+    let mut out = String::new();
+    jal.contextualize(&NoColors, 0x1009c, Some(&context), &mut out)
+        .unwrap();
+    assert_eq!(out, "jal\t0x10350 <memcpy>");
+
+    // auipc/addi, load and store pairs
+    let auipc = decode([0x17, 0x25, 0x00, 0x00]);
+    let show_after = |instr: &Instruction, address: u64| {
+        format!(
+            "{}",
+            instr
+                .display_with(options)
+                .at(address)
+                .with_symbolizer(&symbols)
+                .after(&auipc)
+        )
+    };
+    assert_eq!(show(&auipc, 0x10338), "auipc\ta0,0x2");
+    assert_eq!(
+        show_after(&decode([0x13, 0x05, 0x05, 0xff]), 0x1033c),
+        "addi\ta0,a0,-16 # 12328 <global_var+0x8>"
+    );
+    assert_eq!(
+        show_after(&decode([0x23, 0x22, 0xb5, 0x00]), 0x1033c),
+        "sw\ta1,4(a0) # 1233c <global_var+0x1c>"
+    );
+    assert_eq!(
+        show_after(&decode([0x83, 0x35, 0x85, 0x00]), 0x1033c),
+        "ld\ta1,8(a0) # 12340"
+    );
+    // a different base register
+    assert_eq!(
+        show_after(&decode([0x13, 0x85, 0x05, 0x00]), 0x1033c),
+        "mv\ta0,a1"
+    );
+    // without an address there is nothing to resolve
+    assert_eq!(
+        format!(
+            "{}",
+            decode([0x13, 0x05, 0x05, 0xff])
+                .display_with(options)
+                .after(&auipc)
+        ),
+        "addi\ta0,a0,-16"
+    );
+
+    let auipc = decode([0x17, 0x03, 0x00, 0x00]);
+    let jalr = decode([0xe7, 0x00, 0x03, 0x01]);
+    assert_eq!(
+        format!("{}", jalr.display_with(options).at(0x10004).after(&auipc)),
+        "jalr\t16(t1) # 10010"
+    );
+}
+
+/// A palette that records the style of each piece it writes.
+#[derive(Default)]
+struct Recorder(std::cell::RefCell<Vec<(Style, String)>>);
+
+impl Palette for Recorder {
+    fn paint(
+        &self,
+        style: Style,
+        text: &dyn std::fmt::Display,
+        out: &mut dyn std::fmt::Write,
+    ) -> std::fmt::Result {
+        self.0.borrow_mut().push((style, text.to_string()));
+        write!(out, "{}", text)
+    }
+}
+
+#[test]
+fn test_colorize() {
+    use Style::*;
+
+    let decoder = RiscVDecoder::default().with_extensions(&Extension::COREV);
+    for (data, expected) in [
+        (
+            [0xe3, 0x18, 0xf7, 0xfe],
+            vec![
+                (ControlFlow, "bne"),
+                (Register, "a4"),
+                (Register, "a5"),
+                (Number, "$-0x10"),
+            ],
+        ),
+        (
+            [0xef, 0x00, 0x00, 0x01],
+            vec![(ControlFlow, "jal"), (Number, "$+0x10")],
+        ),
+        (
+            [0x23, 0x3c, 0x11, 0x00],
+            vec![
+                (Data, "sd"),
+                (Register, "ra"),
+                (Number, "24"),
+                (Register, "sp"),
+            ],
+        ),
+        (
+            [0x0b, 0x85, 0x45, 0x00],
+            vec![
+                (Data, "cv.lb"),
+                (Register, "a0"),
+                (Register, "a1"),
+                (Number, "4"),
+            ],
+        ),
+        (
+            [0x73, 0x25, 0x00, 0x30],
+            vec![(Platform, "csrr"), (Register, "a0"), (Platform, "mstatus")],
+        ),
+    ] {
+        let instr = decode_with(&decoder, data);
+        let recorder = Recorder::default();
+        let mut out = String::new();
+        instr
+            .display_with(DisplayOptions::new())
+            .write_to(&mut ColorSink::new(&recorder, &mut out))
+            .unwrap();
+        assert_eq!(out, instr.to_string());
+        let expected: Vec<(Style, String)> = expected
+            .into_iter()
+            .map(|(style, text)| (style, text.to_string()))
+            .collect();
+        assert_eq!(recorder.0.into_inner(), expected, "styles of {:02x?}", data);
+    }
+
+    // addresses, and the symbols naming them, once the address is known
+    let recorder = Recorder::default();
+    let mut out = String::new();
+    decode([0xef, 0x00, 0x00, 0x01])
+        .display_with(DisplayOptions::new())
+        .at(0x1000)
+        .write_to(&mut ColorSink::new(&recorder, &mut out))
+        .unwrap();
+    assert_eq!(
+        recorder.0.into_inner(),
+        vec![
+            (ControlFlow, "jal".to_string()),
+            (Address, "1010".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_tokens() {
+    fn token(kind: TokenKind, span: std::ops::Range<usize>, memory: bool) -> Token {
+        Token { kind, span, memory }
+    }
+
+    let corev = RiscVDecoder::default().with_extensions(&Extension::COREV);
+    let sd = decode([0x23, 0x3c, 0x11, 0x00]);
+    let (text, tokens) = sd.display_with(DisplayOptions::new()).tokens();
+    assert_eq!(text, "sd\tra,24(sp)");
+    assert_eq!(
+        tokens,
+        [
+            token(TokenKind::Mnemonic, 0..2, false),
+            token(TokenKind::Text, 2..3, false),
+            token(TokenKind::Register(Register::X(1)), 3..5, false),
+            token(TokenKind::Text, 5..6, false),
+            token(TokenKind::Immediate(24), 6..8, true),
+            token(TokenKind::Text, 8..9, true),
+            token(TokenKind::Register(Register::X(2)), 9..11, true),
+            token(TokenKind::Text, 11..12, true),
+        ]
+    );
+
+    let csrr = decode([0x73, 0x25, 0x00, 0x30]);
+    let options = DisplayOptions::new().with_register_names(RegisterNames::Numeric);
+    let (text, tokens) = csrr.display_with(options).tokens();
+    assert_eq!(text, "csrr\tx10,mstatus");
+    assert_eq!(tokens[2].kind, TokenKind::Register(Register::X(10)));
+    assert_eq!(tokens[4].kind, TokenKind::Csr(0x300));
+    assert_eq!(&text[tokens[4].span.clone()], "mstatus");
+
+    struct Memcpy;
+    impl Symbolizer for Memcpy {
+        fn symbolize(&self, address: u64) -> Option<(&str, u64)> {
+            Some(("memcpy", address - 0x10350))
+        }
+    }
+    let jal = decode([0xef, 0x00, 0x40, 0x2b]);
+    let (text, tokens) = jal
+        .display_with(DisplayOptions::new())
+        .at(0x1009c)
+        .with_symbolizer(&Memcpy)
+        .tokens();
+    assert_eq!(text, "jal\t10350 <memcpy>");
+    let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::Mnemonic,
+            TokenKind::Text,
+            TokenKind::Address(0x10350),
+            TokenKind::Text,
+            TokenKind::Symbol,
+            TokenKind::Text,
+        ]
+    );
+    assert_eq!(&text[tokens[4].span.clone()], "memcpy");
+
+    // the tokens cover the text, in order, and the text is the displayed instruction
+    for data in [
+        [0x13, 0x01, 0x01, 0xed],
+        [0x37, 0xf5, 0xff, 0xff],
+        [0xe3, 0x18, 0xf7, 0xfe],
+        [0x0f, 0x00, 0x10, 0x03],
+        [0x57, 0x94, 0x46, 0x48],
+        [0x53, 0xa5, 0xc5, 0x20],
+        [0x0b, 0x85, 0x45, 0x00],
+        [0x2b, 0xb5, 0xc5, 0x18],
+    ] {
+        let instr = decode_with(&corev, data);
+        for syntax in [Syntax::Objdump, Syntax::Llvm, Syntax::NoAliases] {
+            let display = instr.display_with(DisplayOptions::new().with_syntax(syntax));
+            let (text, tokens) = display.tokens();
+            assert_eq!(text, format!("{}", display));
+            let mut end = 0;
+            for token in tokens.iter() {
+                assert_eq!(token.span.start, end);
+                end = token.span.end;
+            }
+            assert_eq!(end, text.len());
+        }
+    }
+
+    // post-incremented and indexed memory references
+    let (text, tokens) = decode_with(&corev, [0x0b, 0x85, 0x45, 0x00])
+        .display_with(DisplayOptions::new())
+        .tokens();
+    assert_eq!(text, "cv.lb\ta0,(a1),4");
+    assert!(tokens[..4].iter().all(|t| !t.memory));
+    assert!(tokens[4..].iter().all(|t| t.memory));
+    assert_eq!(tokens.last().unwrap().kind, TokenKind::Immediate(4));
+}
+
+#[test]
+fn test_display_sinks() {
+    let decoder = RiscVDecoder::default().with_extensions(&Extension::COREV);
+    for data in [
+        [0x13, 0x01, 0x01, 0xed],
+        [0x23, 0x3c, 0x11, 0x00],
+        [0x73, 0x25, 0x00, 0x30],
+        [0x0b, 0x85, 0x45, 0x00],
+    ] {
+        let instr = decode_with(&decoder, data);
+        let display = instr.display_with(DisplayOptions::new());
+        let expected = format!("{}", instr);
+
+        let mut text = String::from("> ");
+        display.write_to(&mut text).unwrap();
+        assert_eq!(text, format!("> {}", expected));
+
+        let mut buf = [0u8; 32];
+        let mut sink = BufferSink::new(&mut buf);
+        display.write_to(&mut sink).unwrap();
+        assert_eq!(sink.as_str(), expected);
+        assert_eq!(sink.len(), expected.len());
+
+        let mut operands = Vec::new();
+        let mut index = 0;
+        while let Some(op) = instr.operand_at(index) {
+            operands.push(Some(op));
+            index += 1;
+        }
+        operands.resize(5, None);
+        assert_eq!(operands, instr.operands());
+    }
+
+    // a buffer too small for the text keeps the pieces that fit
+    let instr = decoder
+        .decode(&mut U8Reader::new(&[0x23, 0x3c, 0x11, 0x00][..]))
+        .unwrap();
+    let mut buf = [0u8; 8];
+    let mut sink = BufferSink::new(&mut buf);
+    assert!(instr
+        .display_with(DisplayOptions::new())
+        .write_to(&mut sink)
+        .is_err());
+    assert_eq!(sink.as_str(), "sd\tra,24");
+    sink.clear();
+    assert!(sink.is_empty());
+
+    // and none of a piece written in parts, here `-` and `0x130`
+    let instr = decode_with(&decoder, [0x13, 0x01, 0x01, 0xed]);
+    let mut buf = [0u8; 16];
+    let mut sink = BufferSink::new(&mut buf);
+    assert!(instr
+        .display_with(DisplayOptions::new().with_radix(Radix::Hex))
+        .write_to(&mut sink)
+        .is_err());
+    assert_eq!(sink.as_str(), "addi\tsp,sp,");
+}
+
+/// Counts the heap allocations made on the current thread.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+#[test]
+fn test_display_no_alloc() {
+    let decoder = RiscVDecoder::default().with_extensions(&Extension::COREV);
+    let mut buf = [0u8; 64];
+    for data in [
+        [0x13, 0x01, 0x01, 0xed],
+        [0x23, 0x3c, 0x11, 0x00],
+        [0x73, 0x25, 0x00, 0x30],
+        [0x0b, 0x85, 0x45, 0x00],
+        [0xe3, 0x18, 0xf7, 0xfe],
+    ] {
+        let instr = decode_with(&decoder, data);
+        for options in [
+            DisplayOptions::new(),
+            DisplayOptions::new().with_syntax(Syntax::Llvm),
+            DisplayOptions::new().with_syntax(Syntax::Insn),
+            DisplayOptions::new().with_radix(Radix::Both),
+        ] {
+            let display = instr.display_with(options).at(0x1000);
+            let mut sink = BufferSink::new(&mut buf);
+            let before = ALLOCATIONS.with(|n| n.get());
+            display.write_to(&mut sink).unwrap();
+            let after = ALLOCATIONS.with(|n| n.get());
+            assert_eq!(before, after, "{} allocates", sink.as_str());
+        }
+    }
+}
+
+#[test]
+fn test_display_invalid() {
+    let decoder = RiscVDecoder::default();
+    let decode_into =
+        |instr: &mut Instruction, data: &[u8]| decoder.decode_into(instr, &mut U8Reader::new(data));
+
+    assert_eq!(format!("{}", Instruction::default()), ".word\t0x0");
+    assert_eq!(format!("{}", Opcode::Invalid), ".insn");
+
+    // a failed decode leaves nothing of the instruction decoded before it
+    let mut instr = Instruction::default();
+    decode_into(&mut instr, &[0x13, 0x01, 0x01, 0xed]).unwrap();
+    assert_eq!(format!("{}", instr), "addi\tsp,sp,-304");
+    assert_eq!(
+        decode_into(&mut instr, &[0x6b, 0x00, 0x00, 0x00]),
+        Err(DecodeError::Reserved)
+    );
+    assert_eq!(*instr.opcode(), Opcode::Invalid);
+    assert_eq!(instr.operands(), [None; 5]);
+    assert_eq!(format!("{}", instr), ".word\t0x6b");
+    assert_eq!(instr.len().to_const(), 4);
+
+    // a vendor instruction whose extension is not enabled
+    assert!(matches!(
+        decode_into(&mut instr, &[0x0b, 0xb5, 0x05, 0x3c]),
+        Err(DecodeError::ExtensionNotEnabled(_))
+    ));
+    assert_eq!(format!("{}", instr), ".word\t0x3c05b50b");
+
+    // compressed and truncated instructions are shown as the parcel read
+    assert_eq!(
+        decode_into(&mut instr, &[0x05, 0x00]),
+        Err(DecodeError::Hint)
+    );
+    assert_eq!(format!("{}", instr), ".half\t0x5");
+    assert_eq!(instr.len().to_const(), 2);
+    assert_eq!(
+        decode_into(&mut instr, &[0x13, 0x05]),
+        Err(DecodeError::ExhaustedInput)
+    );
+    assert_eq!(format!("{}", instr), ".half\t0x513");
+
+    // other renderings of invalid instructions do not panic either
+    let options = DisplayOptions::new().with_syntax(Syntax::Llvm);
+    assert_eq!(
+        format!("{}", instr.display_with(options).at(0x1000)),
+        ".half\t0x513"
+    );
+    let (text, tokens) = instr.display_with(options).tokens();
+    assert_eq!(text, ".half\t0x513");
+    assert_eq!(tokens[0].kind, TokenKind::Mnemonic);
+    assert_eq!(tokens[2].kind, TokenKind::Immediate(0x513));
+}
+
+#[test]
+fn test_insn_directives() {
+    let decoder = RiscVDecoder::default();
+    let options = DisplayOptions::new().with_syntax(Syntax::Insn);
+    let insn = |data: &[u8]| {
+        let mut instr = Instruction::default();
+        let _ = decoder.decode_into(&mut instr, &mut U8Reader::new(data));
+        format!("{}", instr.display_with(options))
+    };
+
+    for (data, expected) in [
+        ([0x33, 0x85, 0xc5, 0x00], ".insn\tr OP, 0, 0x0, a0, a1, a2"),
+        ([0x33, 0x85, 0xc5, 0x02], ".insn\tr OP, 0, 0x1, a0, a1, a2"),
+        ([0x03, 0x25, 0x81, 0x00], ".insn\ti LOAD, 2, a0, sp, 8"),
+        (
+            [0x73, 0x25, 0x00, 0xc0],
+            ".insn\ti SYSTEM, 2, a0, zero, -1024",
+        ),
+        ([0x23, 0x2e, 0xa1, 0xfe], ".insn\ts STORE, 2, a0, -4(sp)"),
+        ([0xe3, 0x08, 0xb5, 0xfe], ".insn\tb BRANCH, 0, a0, a1, .-16"),
+        ([0x37, 0x55, 0x34, 0x12], ".insn\tu LUI, a0, 0x12345"),
+        ([0xef, 0x00, 0x10, 0x00], ".insn\tj JAL, ra, .+2048"),
+        (
+            [0x43, 0xf5, 0xc5, 0x68],
+            ".insn\tr4 MADD, 7, 0x0, fa0, fa1, fa2, fa3",
+        ),
+        // a vendor instruction whose extension is not enabled keeps its fields
+        (
+            [0x0b, 0x95, 0xc5, 0x02],
+            ".insn\tr CUSTOM_0, 1, 0x1, a0, a1, a2",
+        ),
+        // a reserved major opcode only has its bits
+        ([0x6b, 0x00, 0x00, 0x00], ".insn\t4, 0x6b"),
+    ] {
+        assert_eq!(insn(&data), expected);
+    }
+
+    // compressed, long and truncated instructions
+    assert_eq!(insn(&[0x05, 0x00]), ".insn\t2, 0x5");
+    assert_eq!(
+        insn(&[0x1f, 0x00, 0x11, 0x22, 0x33, 0x44]),
+        ".insn\t6, 0x44332211001f"
+    );
+    assert_eq!(insn(&[0x13, 0x05]), ".half\t0x513");
+    // an instruction longer than 64 bits is too long for one number
+    assert_eq!(
+        insn(&[0x7f, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]),
+        ".half\t0x7f, 0x2211, 0x4433, 0x6655, 0x8877"
+    );
+
+    // registers are tagged with their file where the fields say which it is
+    let registers = |data: [u8; 4]| {
+        let mut instr = Instruction::default();
+        let _ = decoder.decode_into(&mut instr, &mut U8Reader::new(&data[..]));
+        let (text, tokens) = instr.display_with(options).tokens();
+        let kinds: Vec<TokenKind> = tokens
+            .iter()
+            .map(|token| token.kind)
+            .filter(|kind| matches!(kind, TokenKind::Register(_)))
+            .collect();
+        (text, kinds)
+    };
+    assert_eq!(
+        registers([0x43, 0xf5, 0xc5, 0x68]),
+        (
+            ".insn\tr4 MADD, 7, 0x0, fa0, fa1, fa2, fa3".to_string(),
+            vec![
+                TokenKind::Register(Register::F(10)),
+                TokenKind::Register(Register::F(11)),
+                TokenKind::Register(Register::F(12)),
+                TokenKind::Register(Register::F(13)),
+            ]
+        )
+    );
+    // flw fa0, 8(sp)
+    assert_eq!(
+        registers([0x07, 0x25, 0x81, 0x00]),
+        (
+            ".insn\ti LOAD_FP, 2, fa0, sp, 8".to_string(),
+            vec![
+                TokenKind::Register(Register::F(10)),
+                TokenKind::Register(Register::X(2)),
+            ]
+        )
+    );
+    // fcvt.w.s a0, fa1
+    assert_eq!(
+        registers([0x53, 0xf5, 0x05, 0xc0]),
+        (
+            ".insn\tr OP_FP, 7, 0x60, a0, fa1, ft0".to_string(),
+            vec![
+                TokenKind::Register(Register::X(10)),
+                TokenKind::Register(Register::F(11)),
+                TokenKind::Register(Register::F(0)),
+            ]
+        )
+    );
+    // vector register fields are not tagged
+    assert_eq!(
+        registers([0x57, 0x94, 0x46, 0x48]),
+        (".insn\tr OP_V, 1, 0x24, s0, a3, tp".to_string(), vec![])
+    );
+
+    // branch targets are shown as absolute addresses once the address is known
+    let mut instr = Instruction::default();
+    decoder
+        .decode_into(&mut instr, &mut U8Reader::new(&[0xe3, 0x08, 0xb5, 0xfe]))
+        .unwrap();
+    assert_eq!(
+        format!("{}", instr.display_with(options).at(0x1000)),
+        ".insn\tb BRANCH, 0, a0, a1, 0xff0"
+    );
+    let numeric = options.with_register_names(RegisterNames::Numeric);
+    assert_eq!(
+        format!("{}", instr.display_with(numeric)),
+        ".insn\tb BRANCH, 0, x10, x11, .-16"
+    );
+    let (text, tokens) = instr.display_with(options).tokens();
+    assert_eq!(text, ".insn\tb BRANCH, 0, a0, a1, .-16");
+    assert_eq!(tokens[0].kind, TokenKind::Mnemonic);
+    assert_eq!(tokens[0].span, 0..5);
+    assert!(tokens
+        .iter()
+        .any(|token| token.kind == TokenKind::Register(Register::X(11))));
+}
+
+#[test]
+fn test_listing() {
+    let decoder = RiscVDecoder::default();
+    let mut instr = Instruction::default();
+    decoder
+        .decode_into(&mut instr, &mut U8Reader::new(&[0x33, 0x85, 0xc5, 0x00]))
+        .unwrap();
+    assert_eq!(
+        Listing::new().line(&instr, 0x1000).to_string(),
+        "    1000:\t00c58533          \tadd\ta0,a1,a2"
+    );
+    let listing = Listing::new()
+        .with_address_width(4)
+        .with_encoding_width(8)
+        .with_options(DisplayOptions::new().with_syntax(Syntax::Llvm));
+    assert_eq!(
+        listing.line(&instr, 0x1000).to_string(),
+        "1000:\t00c58533\tadd\ta0, a1, a2"
+    );
+    decoder
+        .decode_into(
+            &mut instr,
+            &mut U8Reader::new(&[0x7f, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]),
+        )
+        .unwrap();
+    assert_eq!(
+        Listing::new().line(&instr, 0x1000).to_string(),
+        "    1000:\t007f 2211 4433 6655 8877\t.half\t0x7f, 0x2211, 0x4433, 0x6655, 0x8877"
+    );
+    decoder
+        .decode_into(
+            &mut instr,
+            &mut U8Reader::new(&[
+                0x7f, 0x10, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa,
+            ]),
+        )
+        .unwrap();
+    assert_eq!(
+        Listing::new().line(&instr, 0x1000).to_string(),
+        "    1000:\t2211107f 66554433 aa998877\t.half\t0x107f, 0x2211, 0x4433, 0x6655, 0x8877, 0xaa99"
+    );
+
+    let data = [
+        // addi sp, sp, -304
+        0x13, 0x01, 0x01, 0xed, //
+        // a compressed hint
+        0x05, 0x00, //
+        // a 48-bit instruction
+        0x1f, 0x00, 0x11, 0x22, 0x33, 0x44, //
+        // a 64-bit instruction
+        0x3f, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, //
+        // auipc a0, 0x1; addi a0, a0, 16
+        0x17, 0x15, 0x00, 0x00, 0x13, 0x05, 0x05, 0x01, //
+        // jal -8
+        0x6f, 0xf0, 0x9f, 0xff, //
+        // a trailing byte
+        0xaa,
+    ];
+    struct Symbols;
+    impl Symbolizer for Symbols {
+        fn symbolize(&self, address: u64) -> Option<(&str, u64)> {
+            (address >= 0x1000).then(|| ("func", address - 0x1000))
+        }
+    }
+    let mut out = String::new();
+    Listing::new()
+        .with_symbolizer(&Symbols)
+        .write_buffer(&decoder, &data, 0x1000, &mut out)
+        .unwrap();
+    let expected = [
+        "    1000:\ted010113          \taddi\tsp,sp,-304",
+        "    1004:\t0005              \t.half\t0x5",
+        "    1006:\t001f 2211 4433    \t.insn\t6, 0x44332211001f",
+        "    100c:\t2211003f 66554433 \t.insn\t8, 0x665544332211003f",
+        "    1014:\t00001517          \tauipc\ta0,0x1",
+        "    1018:\t01050513          \taddi\ta0,a0,16 # 2024 <func+0x1024>",
+        "    101c:\tff9ff06f          \tj\t1014 <func+0x14>",
+        "    1020:\taa                \t.byte\t0xaa",
+    ];
+    assert_eq!(out.lines().collect::<Vec<_>>(), expected);
+
+    let data = [
+        // th.extu, with no vendor extensions enabled
+        0x0b, 0xb5, 0x05, 0x3c, //
+        // addi sp, sp, -304
+        0x13, 0x01, 0x01, 0xed, //
+        // a 48-bit instruction cut off after its second parcel, then an odd byte
+        0x1f, 0x00, 0x11, 0x22, 0xaa,
+    ];
+    let mut out = String::new();
+    Listing::new()
+        .write_buffer(&decoder, &data, 0x1000, &mut out)
+        .unwrap();
+    let expected = [
+        "    1000:\t3c05b50b          \t.word\t0x3c05b50b",
+        "    1004:\ted010113          \taddi\tsp,sp,-304",
+        "    1008:\t001f              \t.half\t0x1f",
+        "    100a:\t2211              \t.half\t0x2211",
+        "    100c:\taa                \t.byte\t0xaa",
+    ];
+    assert_eq!(out.lines().collect::<Vec<_>>(), expected);
+}